@@ -0,0 +1,17 @@
+use soroban_sdk::contracterror;
+
+//
+// Error factory. Sama kayak pool: nomor JANGAN diubah, tambah di belakang.
+//
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    IdenticalTokens = 1,
+    FeeNotEnabled = 2,
+    FeeAlreadyEnabled = 3,
+    InvalidFee = 4,
+    InvalidTickSpacing = 5,
+    PoolAlreadyExists = 6,
+    PoolNotFound = 7,
+}
@@ -0,0 +1,289 @@
+#![no_std]
+// initialize pool memang banyak parameter
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::{
+    contract, contractevent, contractimpl, contracttype, panic_with_error, xdr::ToXdr, Address, Bytes,
+    BytesN, Env,
+};
+
+mod error; // error.rs (contracterror Error)
+mod pool;  // pool.rs (client interface ClmmPool)
+
+pub use error::Error;
+pub use pool::{Pool, PoolClient};
+
+// =============================================================
+//                      STORAGE + EVENT
+// =============================================================
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Owner,
+    PoolWasmHash,
+    FeeTier(u32),               // fee_bps → tick_spacing
+    Pool(Address, Address, u32), // (token0, token1, fee_bps) → pool, token sudah urut
+}
+
+// Event pool baru: topics ("pool_created", token0, token1), data [fee, spacing, pool]
+#[contractevent(topics = ["pool_created"], data_format = "vec")]
+#[derive(Clone, Debug)]
+pub struct PoolCreatedEvent {
+    #[topic]
+    pub token0: Address,
+    #[topic]
+    pub token1: Address,
+    pub fee_bps: u32,
+    pub tick_spacing: i32,
+    pub pool: Address,
+}
+
+// Event fee tier baru: topics ("fee_enabled",), data [fee, spacing]
+#[contractevent(topics = ["fee_enabled"], data_format = "vec")]
+#[derive(Clone, Debug)]
+pub struct FeeEnabledEvent {
+    pub fee_bps: u32,
+    pub tick_spacing: i32,
+}
+
+// Sama kayak pool: fee_bps harus < 10000
+const FEE_BPS_DENOMINATOR: u32 = 10_000;
+
+// tick_spacing gede banget bikin 1 word bitmap kelewat lebar
+const MAX_TICK_SPACING: i32 = 16_384;
+
+// =============================================================
+//                     STORAGE HELPERS
+// =============================================================
+
+fn read_owner(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get::<_, Address>(&DataKey::Owner)
+        .expect("owner not set")
+}
+
+fn read_fee_tier(env: &Env, fee_bps: u32) -> Option<i32> {
+    env.storage()
+        .persistent()
+        .get::<_, i32>(&DataKey::FeeTier(fee_bps))
+}
+
+fn write_fee_tier(env: &Env, fee_bps: u32, tick_spacing: i32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::FeeTier(fee_bps), &tick_spacing);
+}
+
+fn read_pool(env: &Env, token0: &Address, token1: &Address, fee_bps: u32) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get::<_, Address>(&DataKey::Pool(token0.clone(), token1.clone(), fee_bps))
+}
+
+// Token diurutkan by address: token0 < token1. Semua key / salt pakai
+// urutan ini, jadi (A, B) dan (B, A) selalu ketemu pool yang sama.
+fn sort_tokens(token_a: Address, token_b: Address) -> Result<(Address, Address), Error> {
+    if token_a == token_b {
+        return Err(Error::IdenticalTokens);
+    }
+    if token_a < token_b {
+        Ok((token_a, token_b))
+    } else {
+        Ok((token_b, token_a))
+    }
+}
+
+// salt = sha256(xdr(token0) || xdr(token1) || fee_bps big-endian)
+// → address pool deterministik per (pair, fee tier)
+fn pool_salt(env: &Env, token0: &Address, token1: &Address, fee_bps: u32) -> BytesN<32> {
+    let mut data = Bytes::new(env);
+    data.append(&token0.clone().to_xdr(env));
+    data.append(&token1.clone().to_xdr(env));
+    data.extend_from_array(&fee_bps.to_be_bytes());
+    env.crypto().sha256(&data).into()
+}
+
+fn enable_fee_tier(env: &Env, fee_bps: u32, tick_spacing: i32) -> Result<(), Error> {
+    if fee_bps == 0 || fee_bps >= FEE_BPS_DENOMINATOR {
+        return Err(Error::InvalidFee);
+    }
+    if tick_spacing <= 0 || tick_spacing >= MAX_TICK_SPACING {
+        return Err(Error::InvalidTickSpacing);
+    }
+    if read_fee_tier(env, fee_bps).is_some() {
+        return Err(Error::FeeAlreadyEnabled);
+    }
+
+    write_fee_tier(env, fee_bps, tick_spacing);
+    FeeEnabledEvent {
+        fee_bps,
+        tick_spacing,
+    }
+    .publish(env);
+    Ok(())
+}
+
+// =============================================================
+//                      CONTRACT ENTRYPOINT
+// =============================================================
+
+#[contract]
+pub struct ClmmFactory;
+
+#[contractimpl]
+impl ClmmFactory {
+    // ------------------------------------
+    // CONSTRUCTOR
+    // ------------------------------------
+    //
+    // pool_wasm_hash = hash wasm ClmmPool yang sudah di-upload.
+    // Fee tier default (bps → tick_spacing), setara 0.05% / 0.3% / 1%
+    // di Uniswap V3.
+    pub fn __constructor(env: Env, owner: Address, pool_wasm_hash: BytesN<32>) {
+        env.storage().instance().set(&DataKey::Owner, &owner);
+        env.storage()
+            .instance()
+            .set(&DataKey::PoolWasmHash, &pool_wasm_hash);
+
+        // tier default lewat jalur yang sama dengan enable_fee_amount,
+        // biar indexer juga dapet FeeEnabledEvent-nya
+        for (fee_bps, tick_spacing) in [(5u32, 10i32), (30, 60), (100, 200)] {
+            if let Err(e) = enable_fee_tier(&env, fee_bps, tick_spacing) {
+                panic_with_error!(&env, e);
+            }
+        }
+    }
+
+    // ------------------------------------
+    // READERS
+    // ------------------------------------
+
+    pub fn owner(env: Env) -> Address {
+        read_owner(&env)
+    }
+
+    // tick_spacing untuk fee tier, None kalau belum di-enable
+    pub fn fee_tier_tick_spacing(env: Env, fee_bps: u32) -> Option<i32> {
+        read_fee_tier(&env, fee_bps)
+    }
+
+    // Urutan token_a / token_b bebas
+    pub fn get_pool(env: Env, token_a: Address, token_b: Address, fee_bps: u32) -> Option<Address> {
+        let (token0, token1) = sort_tokens(token_a, token_b).ok()?;
+        read_pool(&env, &token0, &token1, fee_bps)
+    }
+
+    // ------------------------------------
+    // CREATE POOL
+    // ------------------------------------
+    //
+    // Siapa aja boleh bikin pool untuk fee tier yang sudah di-enable.
+    //  1. urutkan token (token0 < token1)
+    //  2. deploy ClmmPool di address deterministik (salt pair + fee)
+    //  3. initialize pool dengan admin = factory ini, tick_spacing dari
+    //     fee tier, harga awal dari caller (current_tick harus cocok,
+    //     divalidasi pool)
+    //
+    // sqrt_price_x64 selalu harga token1 per token0 (setelah diurutkan).
+    pub fn create_pool(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        sqrt_price_x64: u128,
+        current_tick: i32,
+    ) -> Result<Address, Error> {
+        let (token0, token1) = sort_tokens(token_a, token_b)?;
+        let tick_spacing = read_fee_tier(&env, fee_bps).ok_or(Error::FeeNotEnabled)?;
+        if read_pool(&env, &token0, &token1, fee_bps).is_some() {
+            return Err(Error::PoolAlreadyExists);
+        }
+
+        let wasm_hash = env
+            .storage()
+            .instance()
+            .get::<_, BytesN<32>>(&DataKey::PoolWasmHash)
+            .expect("pool wasm hash not set");
+
+        let salt = pool_salt(&env, &token0, &token1, fee_bps);
+        let pool = env
+            .deployer()
+            .with_current_contract(salt)
+            .deploy_v2(wasm_hash, ());
+
+        PoolClient::new(&env, &pool).initialize(
+            &env.current_contract_address(),
+            &token0,
+            &token1,
+            &fee_bps,
+            &sqrt_price_x64,
+            &current_tick,
+            &tick_spacing,
+        );
+
+        env.storage().persistent().set(
+            &DataKey::Pool(token0.clone(), token1.clone(), fee_bps),
+            &pool,
+        );
+
+        PoolCreatedEvent {
+            token0,
+            token1,
+            fee_bps,
+            tick_spacing,
+            pool: pool.clone(),
+        }
+        .publish(&env);
+
+        Ok(pool)
+    }
+
+    // ------------------------------------
+    // OWNER
+    // ------------------------------------
+
+    pub fn set_owner(env: Env, new_owner: Address) {
+        read_owner(&env).require_auth();
+        env.storage().instance().set(&DataKey::Owner, &new_owner);
+    }
+
+    // Fee tier baru (ga bisa diubah / dihapus setelah di-enable,
+    // biar pool yang sudah ada tetap konsisten)
+    pub fn enable_fee_amount(env: Env, fee_bps: u32, tick_spacing: i32) -> Result<(), Error> {
+        read_owner(&env).require_auth();
+        enable_fee_tier(&env, fee_bps, tick_spacing)
+    }
+
+    // Pool bikinan factory punya admin = factory, jadi urusan admin
+    // pool (protocol fee) lewat sini, di-gate owner factory.
+    pub fn set_fee_protocol(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        fee_protocol0: u32,
+        fee_protocol1: u32,
+    ) -> Result<(), Error> {
+        read_owner(&env).require_auth();
+        let (token0, token1) = sort_tokens(token_a, token_b)?;
+        let pool = read_pool(&env, &token0, &token1, fee_bps).ok_or(Error::PoolNotFound)?;
+
+        PoolClient::new(&env, &pool).set_fee_protocol(&fee_protocol0, &fee_protocol1);
+        Ok(())
+    }
+
+    pub fn collect_protocol(
+        env: Env,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        recipient: Address,
+    ) -> Result<(i128, i128), Error> {
+        read_owner(&env).require_auth();
+        let (token0, token1) = sort_tokens(token_a, token_b)?;
+        let pool = read_pool(&env, &token0, &token1, fee_bps).ok_or(Error::PoolNotFound)?;
+
+        Ok(PoolClient::new(&env, &pool).collect_protocol(&recipient))
+    }
+}
@@ -0,0 +1,26 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+//
+// Interface ClmmPool yang dipakai factory. Sengaja ga depend ke crate
+// clmm_pool (biar export kontrak pool ga ikut ke-link ke wasm factory),
+// cukup deklarasi ulang entrypoint yang dipanggil.
+//
+// Kalau pool return Error, call dari client ini ikut gagal (revert).
+//
+#[contractclient(name = "PoolClient")]
+pub trait Pool {
+    fn initialize(
+        env: Env,
+        admin: Address,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        sqrt_price_x64: u128,
+        current_tick: i32,
+        tick_spacing: i32,
+    );
+
+    fn set_fee_protocol(env: Env, fee_protocol0: u32, fee_protocol1: u32);
+
+    fn collect_protocol(env: Env, recipient: Address) -> (i128, i128);
+}
@@ -0,0 +1,281 @@
+// Test factory: urutan token, fee tier, gating owner, dan create_pool
+// end to end (deploy + initialize pool beneran).
+//
+// Pool wasm ga di-build di sini. Factory di-deploy dengan hash wasm
+// kosong, ClmmPool native di-register di address deterministik pool,
+// lalu entry instance-nya dihapus: deploy_v2 nulis instance baru dengan
+// hash wasm kosong, dan host nge-dispatch ke ClmmPool native yang sudah
+// ke-register di address itu.
+
+use std::rc::Rc;
+
+use clmm_factory::{ClmmFactory, ClmmFactoryClient, Error};
+use clmm_pool::{math, ClmmPoolClient};
+use soroban_env_host::storage::AccessType;
+use soroban_sdk::{
+    testutils::{Address as _, Events as _, MockAuth, MockAuthInvoke},
+    token,
+    xdr::{self, ToXdr},
+    Address, Bytes, BytesN, Env, FromVal, IntoVal, Val, Vec,
+};
+
+const BIG: i128 = 1_000_000_000_000_000;
+
+struct Setup<'a> {
+    env: Env,
+    owner: Address,
+    factory: ClmmFactoryClient<'a>,
+    token0: Address,
+    token1: Address,
+}
+
+fn setup() -> Setup<'static> {
+    let env = Env::default();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let owner = Address::generate(&env);
+    let empty: BytesN<32> = env.crypto().sha256(&Bytes::new(&env)).into();
+    let factory_id = env.register(ClmmFactory, (owner.clone(), empty));
+
+    let admin = Address::generate(&env);
+    let a = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let b = env.register_stellar_asset_contract_v2(admin).address();
+    let (token0, token1) = if a < b { (a, b) } else { (b, a) };
+
+    Setup {
+        factory: ClmmFactoryClient::new(&env, &factory_id),
+        env,
+        owner,
+        token0,
+        token1,
+    }
+}
+
+impl Setup<'_> {
+    // siapin ClmmPool native di address yang bakal dipakai create_pool
+    fn prepare_pool(&self, fee_bps: u32) -> Address {
+        let env = &self.env;
+        let mut data = Bytes::new(env);
+        data.append(&self.token0.clone().to_xdr(env));
+        data.append(&self.token1.clone().to_xdr(env));
+        data.extend_from_array(&fee_bps.to_be_bytes());
+        let salt: BytesN<32> = env.crypto().sha256(&data).into();
+
+        let pool = env.as_contract(&self.factory.address, || {
+            env.deployer().with_current_contract(salt).deployed_address()
+        });
+        env.register_at(&pool, clmm_pool::ClmmPool, ());
+
+        let contract = xdr::ScAddress::from(&pool);
+        let key = xdr::LedgerKey::ContractData(xdr::LedgerKeyContractData {
+            contract,
+            key: xdr::ScVal::LedgerKeyContractInstance,
+            durability: xdr::ContractDataDurability::Persistent,
+        });
+        env.host()
+            .setup_storage_entry(Rc::new(key), None, AccessType::ReadWrite)
+            .unwrap();
+        pool
+    }
+
+    fn create_pool(&self, fee_bps: u32) -> Address {
+        let pool = self.prepare_pool(fee_bps);
+        // token sengaja dibalik: factory yang ngurutin
+        let created = self.factory.create_pool(&self.token1, &self.token0, &fee_bps, &0, &0);
+        assert_eq!(created, pool);
+        pool
+    }
+
+    // 1 pemanggilan factory.fn_name(args) yang ditandatangani `signer`
+    fn mock_auth(&self, signer: &Address, fn_name: &str, args: Vec<Val>) {
+        self.env.mock_auths(&[MockAuth {
+            address: signer,
+            invoke: &MockAuthInvoke {
+                contract: &self.factory.address,
+                fn_name,
+                args,
+                sub_invokes: &[],
+            },
+        }]);
+    }
+}
+
+#[test]
+fn constructor_enables_default_tiers_with_events() {
+    let env = Env::default();
+    let owner = Address::generate(&env);
+    let empty: BytesN<32> = env.crypto().sha256(&Bytes::new(&env)).into();
+    let factory_id = env.register(ClmmFactory, (owner.clone(), empty));
+
+    // 1 FeeEnabledEvent per tier default, data [fee, spacing]
+    let events = env.events().all();
+    let tiers: std::vec::Vec<(u32, i32)> = events
+        .iter()
+        .filter(|(contract, _, _)| *contract == factory_id)
+        .map(|(_, _, data)| <(u32, i32)>::from_val(&env, &data))
+        .collect();
+    assert_eq!(tiers, [(5, 10), (30, 60), (100, 200)]);
+
+    let factory = ClmmFactoryClient::new(&env, &factory_id);
+    assert_eq!(factory.owner(), owner);
+    assert_eq!(factory.fee_tier_tick_spacing(&5), Some(10));
+    assert_eq!(factory.fee_tier_tick_spacing(&30), Some(60));
+    assert_eq!(factory.fee_tier_tick_spacing(&100), Some(200));
+    assert_eq!(factory.fee_tier_tick_spacing(&50), None);
+}
+
+#[test]
+fn enable_fee_amount_validates_tier() {
+    let s = setup();
+    s.env.mock_all_auths();
+
+    assert_eq!(s.factory.try_enable_fee_amount(&0, &10), Err(Ok(Error::InvalidFee)));
+    assert_eq!(s.factory.try_enable_fee_amount(&10_000, &10), Err(Ok(Error::InvalidFee)));
+    assert_eq!(s.factory.try_enable_fee_amount(&50, &0), Err(Ok(Error::InvalidTickSpacing)));
+    assert_eq!(s.factory.try_enable_fee_amount(&50, &-10), Err(Ok(Error::InvalidTickSpacing)));
+    assert_eq!(s.factory.try_enable_fee_amount(&50, &16_384), Err(Ok(Error::InvalidTickSpacing)));
+    assert_eq!(s.factory.try_enable_fee_amount(&30, &10), Err(Ok(Error::FeeAlreadyEnabled)));
+
+    // batas yang masih valid
+    s.factory.enable_fee_amount(&9_999, &16_383);
+    s.factory.enable_fee_amount(&1, &1);
+    assert_eq!(s.factory.fee_tier_tick_spacing(&9_999), Some(16_383));
+    assert_eq!(s.factory.fee_tier_tick_spacing(&1), Some(1));
+    assert_eq!(s.factory.try_enable_fee_amount(&1, &1), Err(Ok(Error::FeeAlreadyEnabled)));
+}
+
+#[test]
+fn enable_fee_amount_requires_owner() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
+
+    s.mock_auth(&stranger, "enable_fee_amount", (50u32, 100i32).into_val(&s.env));
+    assert!(matches!(s.factory.try_enable_fee_amount(&50, &100), Err(Err(_))));
+
+    s.mock_auth(&s.owner, "enable_fee_amount", (50u32, 100i32).into_val(&s.env));
+    s.factory.enable_fee_amount(&50, &100);
+    assert_eq!(s.factory.fee_tier_tick_spacing(&50), Some(100));
+}
+
+#[test]
+fn create_pool_end_to_end() {
+    let s = setup();
+    s.env.mock_all_auths();
+
+    assert_eq!(s.factory.get_pool(&s.token0, &s.token1, &30), None);
+    let pool_id = s.create_pool(30);
+
+    // (A, B) dan (B, A) ketemu pool yang sama
+    assert_eq!(s.factory.get_pool(&s.token0, &s.token1, &30), Some(pool_id.clone()));
+    assert_eq!(s.factory.get_pool(&s.token1, &s.token0, &30), Some(pool_id.clone()));
+    assert_eq!(s.factory.get_pool(&s.token0, &s.token1, &5), None);
+    assert_eq!(s.factory.get_pool(&s.token0, &s.token0, &30), None);
+
+    // pool sudah di-initialize: admin = factory, token urut, spacing dari tier
+    let pool = ClmmPoolClient::new(&s.env, &pool_id);
+    let config = pool.get_pool_config();
+    assert_eq!(config.admin, s.factory.address);
+    assert_eq!(config.fee_bps, 30);
+    let ps = pool.get_pool_state();
+    assert_eq!((ps.token0, ps.token1), (s.token0.clone(), s.token1.clone()));
+    assert_eq!((ps.tick_spacing, ps.current_tick), (60, 0));
+
+    // pool beneran jalan: add liquidity + swap
+    let user = Address::generate(&s.env);
+    for t in [&s.token0, &s.token1] {
+        token::StellarAssetClient::new(&s.env, t).mint(&user, &BIG);
+    }
+    pool.add_liquidity(&user, &-600, &600, &1_000_000_000_000, &i128::MAX, &i128::MAX, &u64::MAX);
+    let limit = math::tick_to_sqrt_price_x64(&s.env, -600).unwrap();
+    let r = pool.swap(&user, &1_000_000, &true, &limit, &0, &i128::MAX, &u64::MAX);
+    assert_eq!(r.amount_in, 1_000_000);
+    assert!(pool.get_pool_state().current_tick < 0);
+
+    // pool kedua pair yang sama beda fee → address beda
+    let pool_100 = s.create_pool(100);
+    assert_ne!(pool_100, pool_id);
+    assert_eq!(ClmmPoolClient::new(&s.env, &pool_100).get_pool_state().tick_spacing, 200);
+}
+
+#[test]
+fn create_pool_rejects_bad_input() {
+    let s = setup();
+    s.env.mock_all_auths();
+
+    assert_eq!(
+        s.factory.try_create_pool(&s.token0, &s.token0, &30, &0, &0),
+        Err(Ok(Error::IdenticalTokens))
+    );
+    assert_eq!(
+        s.factory.try_create_pool(&s.token0, &s.token1, &50, &0, &0),
+        Err(Ok(Error::FeeNotEnabled))
+    );
+
+    s.create_pool(30);
+    assert_eq!(
+        s.factory.try_create_pool(&s.token0, &s.token1, &30, &0, &0),
+        Err(Ok(Error::PoolAlreadyExists))
+    );
+    assert_eq!(
+        s.factory.try_create_pool(&s.token1, &s.token0, &30, &0, &0),
+        Err(Ok(Error::PoolAlreadyExists))
+    );
+
+    // proxy admin ke pool yang belum ada
+    assert_eq!(
+        s.factory.try_set_fee_protocol(&s.token0, &s.token1, &5, &4, &4),
+        Err(Ok(Error::PoolNotFound))
+    );
+}
+
+#[test]
+fn protocol_fee_proxies_are_owner_gated() {
+    let s = setup();
+    s.env.mock_all_auths();
+    let pool_id = s.create_pool(30);
+    let pool = ClmmPoolClient::new(&s.env, &pool_id);
+
+    let stranger = Address::generate(&s.env);
+    let treasury = Address::generate(&s.env);
+
+    // bukan owner → gagal auth, fee protocol tetap 0
+    let args = (&s.token0, &s.token1, 30u32, 4u32, 4u32).into_val(&s.env);
+    s.mock_auth(&stranger, "set_fee_protocol", args);
+    assert!(matches!(
+        s.factory.try_set_fee_protocol(&s.token0, &s.token1, &30, &4, &4),
+        Err(Err(_))
+    ));
+    assert_eq!(pool.get_pool_state().fee_protocol0, 0);
+
+    // owner, token dibalik juga boleh
+    let args = (&s.token1, &s.token0, 30u32, 4u32, 4u32).into_val(&s.env);
+    s.mock_auth(&s.owner, "set_fee_protocol", args);
+    s.factory.set_fee_protocol(&s.token1, &s.token0, &30, &4, &4);
+    let ps = pool.get_pool_state();
+    assert_eq!((ps.fee_protocol0, ps.fee_protocol1), (4, 4));
+
+    // bikin protocol fee token0
+    s.env.mock_all_auths();
+    let user = Address::generate(&s.env);
+    for t in [&s.token0, &s.token1] {
+        token::StellarAssetClient::new(&s.env, t).mint(&user, &BIG);
+    }
+    pool.add_liquidity(&user, &-600, &600, &1_000_000_000_000, &i128::MAX, &i128::MAX, &u64::MAX);
+    let limit = math::tick_to_sqrt_price_x64(&s.env, -600).unwrap();
+    pool.swap(&user, &1_000_000, &true, &limit, &0, &i128::MAX, &u64::MAX);
+    let owed = pool.get_pool_state().protocol_fees0;
+    assert!(owed > 0);
+
+    let args = (&s.token0, &s.token1, 30u32, &stranger).into_val(&s.env);
+    s.mock_auth(&stranger, "collect_protocol", args);
+    assert!(matches!(
+        s.factory.try_collect_protocol(&s.token0, &s.token1, &30, &stranger),
+        Err(Err(_))
+    ));
+
+    let args = (&s.token0, &s.token1, 30u32, &treasury).into_val(&s.env);
+    s.mock_auth(&s.owner, "collect_protocol", args);
+    assert_eq!(s.factory.collect_protocol(&s.token0, &s.token1, &30, &treasury), (owed, 0));
+    assert_eq!(token::Client::new(&s.env, &s.token0).balance(&treasury), owed);
+    assert_eq!(pool.get_pool_state().protocol_fees0, 0);
+}
@@ -0,0 +1,32 @@
+use soroban_sdk::{contractclient, Bytes, Env};
+
+//
+// Interface yang WAJIB diimplement kontrak caller untuk
+// swap_with_callback. Pool panggil ini SETELAH token_out dikirim.
+//
+// amount0_delta / amount1_delta dari sisi pool:
+//   > 0 → pool harus NERIMA segini (caller wajib transfer ke pool)
+//   < 0 → pool sudah KIRIM segini ke recipient
+//
+// Setelah callback balik, pool cek saldo token_in-nya naik minimal
+// sebesar delta positif. Kurang → seluruh swap di-revert.
+//
+// NOTE: Soroban ga izinkan re-entry ke kontrak yang sama, jadi
+// callback ga bisa manggil balik pool di tengah swap.
+//
+#[contractclient(name = "SwapCallbackClient")]
+pub trait SwapCallback {
+    fn swap_callback(env: Env, amount0_delta: i128, amount1_delta: i128, data: Bytes);
+}
+
+//
+// Interface untuk penerima flash loan. Pool panggil ini SETELAH
+// amount0 / amount1 dikirim ke recipient.
+//
+// fee0 / fee1 = fee yang wajib dibayar di atas pinjaman. Sebelum
+// callback balik, recipient harus transfer (amount + fee) ke pool.
+//
+#[contractclient(name = "FlashCallbackClient")]
+pub trait FlashCallback {
+    fn flash_callback(env: Env, fee0: i128, fee1: i128, data: Bytes);
+}
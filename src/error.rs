@@ -0,0 +1,58 @@
+use soroban_sdk::contracterror;
+
+//
+// Error kontrak. Kode u32 ini yang dilihat client (frontend / bot),
+// jadi JANGAN ubah nomor yang sudah ada — tambah di belakang.
+//
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    // --- state pool ---
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+
+    // --- parameter initialize / admin ---
+    IdenticalTokens = 3,
+    InvalidTickSpacing = 4,
+    InvalidFee = 5,
+    InvalidPrice = 6,
+    InvalidFeeProtocol = 7,
+
+    // --- input umum ---
+    InvalidAmount = 8,
+    InvalidLiquidity = 9,
+
+    // --- tick ---
+    InvalidTickRange = 10,
+    TickOutOfRange = 11,
+    TickNotAligned = 12,
+    TickLiquidityOverflow = 13,
+
+    // --- posisi / auth ---
+    InsufficientLiquidity = 14,
+    Unauthorized = 15,
+    InvalidOperator = 16,
+
+    // --- swap / slippage ---
+    SlippageExceeded = 17,
+    ZeroAmountOut = 18,
+
+    // --- math ---
+    MathOverflow = 19,
+    AmountOverflow = 20,
+
+    // --- swap (lanjutan) ---
+    InvalidPriceLimit = 21,
+    DeadlineExpired = 22,
+    InsufficientInputAmount = 23,
+    NoActiveLiquidity = 24,
+
+    // --- oracle ---
+    ObservationTooOld = 25,
+    InvalidCardinality = 26,
+    TickNotInitialized = 27,
+
+    // --- initialize (lanjutan) ---
+    TokensNotSorted = 28,
+}
@@ -1,14 +1,23 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, Symbol};
+// entrypoint kontrak memang banyak parameter (slippage, deadline, dst)
+#![allow(clippy::too_many_arguments)]
+use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Bytes, Env, Vec};
 
 
+mod error; // error.rs (contracterror Error)
 mod tick;  // tick.rs (TickInfo + tick logic)
 mod pool;  // pool.rs (PoolState + init_pool + helpers)
 mod swap;  // swap.rs (swap loop)
+mod callback; // callback.rs (interface swap_callback / flash_callback)
+mod oracle; // oracle.rs (TWAP observation ring buffer)
+pub mod math;  // math.rs (tick math + swap step math, dipakai juga di tests/)
 
+pub use error::Error;
 pub use pool::*;
 pub use tick::TickInfo;
 pub use swap::SwapResult;
+pub use oracle::Observation;
+pub use callback::{FlashCallback, FlashCallbackClient, SwapCallback, SwapCallbackClient};
 
 // =============================================================
 //                    POOL CONFIG + POSITION
@@ -27,8 +36,12 @@ pub struct PoolConfig {
 #[contracttype]
 pub struct Position {
     pub liquidity: i128,
-    pub token_a_amount: i128,
-    pub token_b_amount: i128,
+    // fee growth di dalam range waktu terakhir posisi di-settle
+    pub fee_growth_inside0_last_x64: u128,
+    pub fee_growth_inside1_last_x64: u128,
+    // fee + token hasil burn yang sudah jadi hak owner, diambil lewat collect
+    pub tokens_owed0: i128,
+    pub tokens_owed1: i128,
 }
 
 #[derive(Clone)]
@@ -38,7 +51,10 @@ pub enum DataKey {
     PoolConfig,
     Initialized,
     Tick(i32),
+    TickBitmap(i32),
     Position(Address, i32, i32),
+    Operator(Address, Address), // (owner, operator) → boleh kelola semua posisi owner
+    Observation(u32),           // slot ring buffer oracle
 }
 
 // =============================================================
@@ -53,11 +69,11 @@ fn write_pool_config(env: &Env, cfg: &PoolConfig) {
     env.storage().persistent().set(&DataKey::PoolConfig, cfg);
 }
 
-fn read_pool_config(env: &Env) -> PoolConfig {
+fn read_pool_config(env: &Env) -> Result<PoolConfig, Error> {
     env.storage()
         .persistent()
         .get::<_, PoolConfig>(&DataKey::PoolConfig)
-        .expect("pool config not initialized")
+        .ok_or(Error::NotInitialized)
 }
 
 fn write_position(env: &Env, owner: &Address, lower: i32, upper: i32, pos: &Position) {
@@ -72,40 +88,138 @@ fn read_position(env: &Env, owner: &Address, lower: i32, upper: i32) -> Position
         .get::<_, Position>(&DataKey::Position(owner.clone(), lower, upper))
         .unwrap_or(Position {
             liquidity: 0,
-            token_a_amount: 0,
-            token_b_amount: 0,
+            fee_growth_inside0_last_x64: 0,
+            fee_growth_inside1_last_x64: 0,
+            tokens_owed0: 0,
+            tokens_owed1: 0,
         })
 }
 
-// TickInfo helpers
-fn read_tick_info_persistent(env: &Env, tick: i32) -> TickInfo {
+// PoolState helpers
+fn read_pool_state(env: &Env) -> Result<PoolState, Error> {
     env.storage()
         .persistent()
-        .get::<_, TickInfo>(&DataKey::Tick(tick))
-        .unwrap_or(TickInfo {
-            liquidity_gross: 0,
-            liquidity_net: 0,
-        })
+        .get::<_, PoolState>(&DataKey::PoolState)
+        .ok_or(Error::NotInitialized)
 }
 
-fn write_tick_info_persistent(env: &Env, tick: i32, info: &TickInfo) {
+fn write_pool_state(env: &Env, state: &PoolState) {
     env.storage()
         .persistent()
-        .set(&DataKey::Tick(tick), info);
+        .set(&DataKey::PoolState, state);
 }
 
-// PoolState helpers
-fn read_pool_state(env: &Env) -> PoolState {
+// Operator: address yang di-approve owner buat kelola posisinya
+// (remove_liquidity / collect), misal kontrak vault.
+fn read_operator(env: &Env, owner: &Address, operator: &Address) -> bool {
     env.storage()
         .persistent()
-        .get::<_, PoolState>(&DataKey::PoolState)
-        .expect("pool not initialized")
+        .get::<_, bool>(&DataKey::Operator(owner.clone(), operator.clone()))
+        .unwrap_or(false)
 }
 
-fn write_pool_state(env: &Env, state: &PoolState) {
-    env.storage()
-        .persistent()
-        .set(&DataKey::PoolState, state);
+fn write_operator(env: &Env, owner: &Address, operator: &Address, approved: bool) {
+    let key = DataKey::Operator(owner.clone(), operator.clone());
+    if approved {
+        env.storage().persistent().set(&key, &true);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+// caller harus owner sendiri atau operator yang di-approve owner
+fn require_owner_or_operator(env: &Env, caller: &Address, owner: &Address) -> Result<(), Error> {
+    caller.require_auth();
+    if caller != owner && !read_operator(env, owner, caller) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+// Tx yang nyangkut lama (harga sudah basi) ditolak.
+// deadline = ledger timestamp (detik, unix) terakhir yang masih boleh.
+fn check_deadline(env: &Env, deadline: u64) -> Result<(), Error> {
+    if env.ledger().timestamp() > deadline {
+        return Err(Error::DeadlineExpired);
+    }
+    Ok(())
+}
+
+// Posisi [lower, upper) ikut liquidity aktif kalau current_tick di dalamnya
+fn is_in_range(state: &PoolState, lower: i32, upper: i32) -> bool {
+    state.current_tick >= lower && state.current_tick < upper
+}
+
+// Settle fee posisi: fee growth inside sejak settle terakhir × liquidity
+// posisi → tokens_owed. Wajib dipanggil SEBELUM pos.liquidity berubah,
+// dan pas tick lower/upper masih ada di storage.
+fn settle_position_fees(
+    env: &Env,
+    state: &PoolState,
+    pos: &mut Position,
+    lower: i32,
+    upper: i32,
+) -> Result<(), Error> {
+    let (inside0, inside1) = tick::get_fee_growth_inside(
+        env,
+        lower,
+        upper,
+        state.current_tick,
+        state.fee_growth_global0_x64,
+        state.fee_growth_global1_x64,
+    );
+
+    let owed0 = math::fees_owed(
+        env,
+        inside0.wrapping_sub(pos.fee_growth_inside0_last_x64),
+        pos.liquidity,
+    )?;
+    let owed1 = math::fees_owed(
+        env,
+        inside1.wrapping_sub(pos.fee_growth_inside1_last_x64),
+        pos.liquidity,
+    )?;
+
+    pos.tokens_owed0 += owed0;
+    pos.tokens_owed1 += owed1;
+    pos.fee_growth_inside0_last_x64 = inside0;
+    pos.fee_growth_inside1_last_x64 = inside1;
+    Ok(())
+}
+
+// Token yang masuk di luar kurva swap (fee flash, kelebihan bayar
+// swap_callback): 1/fee_protocol ke protocol_fees, sisanya ke LP
+// in-range lewat fee_growth_global. Kalau liquidity aktif 0 semuanya
+// ke protocol_fees, biar ga nyangkut di pool tanpa pemilik.
+fn credit_fees(
+    env: &Env,
+    ps: &mut PoolState,
+    amount0: i128,
+    amount1: i128,
+) -> Result<(), Error> {
+    let mut lp_fee0 = amount0;
+    let mut lp_fee1 = amount1;
+    if ps.liquidity <= 0 {
+        lp_fee0 = 0;
+        lp_fee1 = 0;
+    } else {
+        if ps.fee_protocol0 > 0 {
+            lp_fee0 -= amount0 / ps.fee_protocol0 as i128;
+        }
+        if ps.fee_protocol1 > 0 {
+            lp_fee1 -= amount1 / ps.fee_protocol1 as i128;
+        }
+    }
+    ps.protocol_fees0 += amount0 - lp_fee0;
+    ps.protocol_fees1 += amount1 - lp_fee1;
+
+    ps.fee_growth_global0_x64 = ps
+        .fee_growth_global0_x64
+        .wrapping_add(math::fee_growth_delta_x64(env, lp_fee0, ps.liquidity)?);
+    ps.fee_growth_global1_x64 = ps
+        .fee_growth_global1_x64
+        .wrapping_add(math::fee_growth_delta_x64(env, lp_fee1, ps.liquidity)?);
+    Ok(())
 }
 
 // =============================================================
@@ -130,23 +244,38 @@ pub fn initialize(
     sqrt_price_x64: u128,
     current_tick: i32,
     tick_spacing: i32,
-) {
+) -> Result<(), Error> {
     // 1️⃣ Cegah double-initialize
     if env.storage().persistent().has(&DataKey::Initialized) {
-        panic!("pool already initialized");
+        return Err(Error::AlreadyInitialized);
     }
 
+    // admin wajib tanda tangan (factory lolos implisit karena manggil
+    // langsung). Tapi admin dipilih caller sendiri, jadi ini BUKAN proteksi
+    // front-run: pool hasil deploy manual bisa di-init duluan orang lain.
+    // Yang aman cuma pool dari factory (deploy + init atomic di
+    // create_pool) atau deploy manual yang init di transaksi yang sama.
+    admin.require_auth();
+
     // 2️⃣ Validasi basic (biar ga keisi data aneh)
     if token_a == token_b {
-        panic!("token_a and token_b must be different");
+        return Err(Error::IdenticalTokens);
+    }
+
+    // Token harus urut by address (token_a < token_b), sama kayak
+    // factory. Jadi satu pair selalu punya konvensi harga yang sama:
+    // sqrt_price_x64 = harga token_b per token_a.
+    if token_a > token_b {
+        return Err(Error::TokensNotSorted);
     }
 
     if tick_spacing <= 0 {
-        panic!("tick_spacing must be > 0");
+        return Err(Error::InvalidTickSpacing);
     }
 
-    if fee_bps == 0 {
-        panic!("fee_bps must be > 0");
+    // fee_bps harus 1..10000 (exclusive)
+    if fee_bps == 0 || fee_bps as u128 >= math::FEE_BPS_DENOMINATOR {
+        return Err(Error::InvalidFee);
     }
 
     // 3️⃣ Tentukan harga awal
@@ -157,13 +286,23 @@ pub fn initialize(
         sqrt_price_x64
     };
 
+    //    Harga harus di dalam range tick global, dan current_tick harus
+    //    konsisten sama harga, karena liquidity aktif ditentukan dari
+    //    current_tick (posisi in-range atau tidak)
+    if !(math::MIN_SQRT_PRICE_X64..=math::MAX_SQRT_PRICE_X64).contains(&initial_sqrt_price_x64) {
+        return Err(Error::InvalidPrice);
+    }
+    if current_tick != math::sqrt_price_to_tick(&env, initial_sqrt_price_x64)? {
+        return Err(Error::InvalidPrice);
+    }
+
     // 4️⃣ Inisialisasi PoolState (di pool.rs)
     //
     //    init_pool bertugas:
     //    - set sqrt_price_x64
     //    - set current_tick
     //    - set tick_spacing
-    //    - set token_a/token_b
+    //    - set token0/token1 (= token_a/token_b, sudah urut)
     //    - set liquidity awal = 0
     init_pool(
         &env,
@@ -172,7 +311,7 @@ pub fn initialize(
         tick_spacing,
         token_a.clone(),
         token_b.clone(),
-    );
+    )?;
 
     // 5️⃣ Simpan PoolConfig (admin, fee, token)
     let cfg = PoolConfig {
@@ -187,22 +326,24 @@ pub fn initialize(
     env.storage()
         .persistent()
         .set(&DataKey::Initialized, &true);
+
+    Ok(())
 }
 
     // ------------------------------------
     // READERS
     // ------------------------------------
 
-    pub fn get_pool_state(env: Env) -> PoolState {
+    pub fn get_pool_state(env: Env) -> Result<PoolState, Error> {
         read_pool_state(&env)
     }
 
-    pub fn get_pool_config(env: Env) -> PoolConfig {
+    pub fn get_pool_config(env: Env) -> Result<PoolConfig, Error> {
         read_pool_config(&env)
     }
 
     pub fn get_tick_info(env: Env, tick: i32) -> TickInfo {
-        read_tick_info_persistent(&env, tick)
+        tick::read_tick_info(&env, tick)
     }
 
     pub fn get_position(env: Env, owner: Address, lower: i32, upper: i32) -> Position {
@@ -213,199 +354,553 @@ pub fn initialize(
     // SWAP ENTRYPOINT
     // ------------------------------------
 
-     // ------------------------------------
-    // SWAP ENTRYPOINT (PHASE 2 - SIMPLE PRICE MOVE, NO TICK CROSS)
-    // ------------------------------------
-
-    // ------------------------------------
-    // SWAP ENTRYPOINT (PHASE 2 - SIMPLE PRICE MOVE, NO TICK CROSS)
-    // ------------------------------------
-
+    // amount_specified:
+    //   > 0 → exact input: jual persis segini token_in (incl. fee)
+    //   < 0 → exact output: terima persis -amount_specified token_out,
+    //         input yang dibutuhkan dihitung pool (batasi pakai amount_in_max)
+    //
+    // sqrt_price_limit_x64 = batas harga (Q64.64), swap berhenti di situ
+    // walau amount belum habis (partial fill):
+    //   zero_for_one = true  → limit < harga sekarang, > MIN_SQRT_PRICE_X64
+    //   zero_for_one = false → limit > harga sekarang, < MAX_SQRT_PRICE_X64
+    // Yang ditarik dari caller cuma amount_in yang beneran kepakai.
+    //
+    // Proteksi slippage: amount_out < amount_out_min atau
+    // amount_in > amount_in_max → SlippageExceeded. Lewat deadline
+    // (ledger timestamp) → DeadlineExpired.
     pub fn swap(
         env: Env,
         caller: Address,
         amount_specified: i128,
         zero_for_one: bool,
-        _sqrt_price_limit_x64: u128, // belum dipakai di Phase 2
-    ) -> SwapResult {
+        sqrt_price_limit_x64: u128,
+        amount_out_min: i128,
+        amount_in_max: i128,
+        deadline: u64,
+    ) -> Result<SwapResult, Error> {
         caller.require_auth();
-
-        if amount_specified <= 0 {
-            panic!("amount_specified must be > 0");
-        }
+        check_deadline(&env, deadline)?;
 
         // Baca config & state pool
-        let cfg = read_pool_config(&env);
-        let mut pool = read_pool_state(&env);
+        let cfg = read_pool_config(&env)?;
         let pool_addr = pool_address(&env);
 
-        if pool.liquidity <= 0 {
-            panic!("no liquidity in pool");
+        // Tentukan token_in / token_out
+        // zero_for_one = true  => token0 (token_a) -> token1 (token_b)
+        // zero_for_one = false => token1 (token_b) -> token0 (token_a)
+        let (token_in, token_out) = if zero_for_one {
+            (cfg.token_a.clone(), cfg.token_b.clone())
+        } else {
+            (cfg.token_b.clone(), cfg.token_a.clone())
+        };
+
+        // Swap loop (swap.rs) → update PoolState + event
+        let result = swap::swap(
+            &env,
+            caller.clone(),
+            amount_specified,
+            zero_for_one,
+            sqrt_price_limit_x64,
+            cfg.fee_bps,
+        )?;
+
+        if result.amount_out <= 0 {
+            return Err(Error::ZeroAmountOut);
+        }
+        if result.amount_out < amount_out_min || result.amount_in > amount_in_max {
+            return Err(Error::SlippageExceeded);
         }
 
-        // Tentukan token_in / token_out
-        // zero_for_one = true  => token_a (XLM) -> token_b (USDC)
-        // zero_for_one = false => token_b (USDC) -> token_a (XLM)
+        // Transfer token:
+        // 1) user kirim token_in ke pool (amount_in termasuk fee)
+        token::Client::new(&env, &token_in).transfer(&caller, &pool_addr, &result.amount_in);
+
+        // 2) pool kirim token_out ke user (amount_out dari kurva)
+        token::Client::new(&env, &token_out).transfer(&pool_addr, &caller, &result.amount_out);
+
+        Ok(result)
+    }
+
+    // ------------------------------------
+    // SWAP DENGAN CALLBACK (bayar belakangan)
+    // ------------------------------------
+    //
+    // Untuk kontrak router / arbitrase. Urutan:
+    //  1. swap loop (sama kayak swap biasa)
+    //  2. pool kirim token_out ke recipient DULU
+    //  3. pool panggil caller.swap_callback(amount0_delta, amount1_delta, data)
+    //  4. pool cek saldo token_in naik >= amount_in, kalau ga → revert.
+    //     Kelebihan bayar masuk fee (protocol + LP in-range)
+    //
+    // caller harus kontrak yang implement SwapCallback (callback.rs).
+    // Slippage dicek sendiri oleh caller di dalam callback.
+    pub fn swap_with_callback(
+        env: Env,
+        caller: Address,
+        recipient: Address,
+        amount_specified: i128,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: u128,
+        data: Bytes,
+        deadline: u64,
+    ) -> Result<SwapResult, Error> {
+        caller.require_auth();
+        check_deadline(&env, deadline)?;
+
+        let cfg = read_pool_config(&env)?;
+        let pool_addr = pool_address(&env);
+
         let (token_in, token_out) = if zero_for_one {
             (cfg.token_a.clone(), cfg.token_b.clone())
         } else {
             (cfg.token_b.clone(), cfg.token_a.clone())
         };
 
-        // Hitung fee & amount_out (versi fixed price, 1:1 - fee)
-        let amount_in: i128 = amount_specified;
-        let fee_bps_i128: i128 = cfg.fee_bps as i128;
+        let result = swap::swap(
+            &env,
+            caller.clone(),
+            amount_specified,
+            zero_for_one,
+            sqrt_price_limit_x64,
+            cfg.fee_bps,
+        )?;
+
+        if result.amount_out <= 0 {
+            return Err(Error::ZeroAmountOut);
+        }
+
+        // 1) kirim token_out dulu
+        token::Client::new(&env, &token_out).transfer(&pool_addr, &recipient, &result.amount_out);
+
+        // 2) callback: delta dari sisi pool (+ = pool nerima, - = pool kirim)
+        let (amount0_delta, amount1_delta) = if zero_for_one {
+            (result.amount_in, -result.amount_out)
+        } else {
+            (-result.amount_out, result.amount_in)
+        };
+
+        let token_in_client = token::Client::new(&env, &token_in);
+        let balance_before = token_in_client.balance(&pool_addr);
 
-        let fee: i128 = amount_in * fee_bps_i128 / 10_000;
-        let amount_after_fee: i128 = amount_in - fee;
+        SwapCallbackClient::new(&env, &caller).swap_callback(&amount0_delta, &amount1_delta, &data);
 
-        if amount_after_fee <= 0 {
-            panic!("amount_after_fee must be > 0");
+        // 3) verifikasi pembayaran
+        let paid = token_in_client.balance(&pool_addr) - balance_before;
+        if paid < result.amount_in {
+            return Err(Error::InsufficientInputAmount);
         }
 
-        // Untuk Phase 2: harga masih sederhana → amount_out = amount_after_fee (1:1)
-        let amount_out: i128 = amount_after_fee;
+        // 4) kelebihan bayar dianggap fee (sama kayak fee flash)
+        let excess = paid - result.amount_in;
+        if excess > 0 {
+            let mut ps = read_pool_state(&env)?;
+            if zero_for_one {
+                credit_fees(&env, &mut ps, excess, 0)?;
+            } else {
+                credit_fees(&env, &mut ps, 0, excess)?;
+            }
+            write_pool_state(&env, &ps);
+        }
 
-        // Transfer token:
-        // 1) user kirim token_in ke pool (full amount_in, fee ikut masuk pool)
-        token::Client::new(&env, &token_in).transfer(&caller, &pool_addr, &amount_in);
-
-        // 2) pool kirim token_out ke user (amount_out)
-        token::Client::new(&env, &token_out).transfer(&pool_addr, &caller, &amount_out);
-
-        // ----------------------------------------------------
-        // PRICE MATH DIPINDAH KE swap.rs (apply_simple_price_move)
-        // ----------------------------------------------------
-        swap::apply_simple_price_move(&mut pool, amount_in, zero_for_one);
-
-        // Simpan state baru
-        write_pool_state(&env, &pool);
-
-        // Event swap pakai nilai state terbaru
-        env.events().publish(
-            (Symbol::new(&env, "swap"), caller.clone()),
-            (
-                amount_in,
-                amount_out,
-                pool.current_tick,
-                pool.sqrt_price_x64,
-            ),
-        );
-
-        SwapResult {
-            amount_in,
-            amount_out,
-            current_tick: pool.current_tick,
-            sqrt_price_x64: pool.sqrt_price_x64,
+        Ok(result)
+    }
+
+    // ------------------------------------
+    // FLASH LOAN
+    // ------------------------------------
+    //
+    // Pinjam token_a / token_b dari reserve pool dalam satu transaksi:
+    //  1. pool kirim amount0 / amount1 ke recipient
+    //  2. pool panggil recipient.flash_callback(fee0, fee1, data)
+    //  3. saldo pool harus naik >= fee (pinjaman + fee sudah balik)
+    //
+    // fee = ceil(amount * fee_bps / 10000). Fee yang dibayar (dipotong
+    // bagian protocol) dibagi ke LP in-range lewat fee_growth_global.
+    // Butuh liquidity aktif > 0, karena fee ga ada yang nerima kalau 0.
+    //
+    // Return: (paid0, paid1) fee yang beneran dibayar.
+    pub fn flash(
+        env: Env,
+        recipient: Address,
+        amount0: i128,
+        amount1: i128,
+        data: Bytes,
+    ) -> Result<(i128, i128), Error> {
+        recipient.require_auth();
+        if amount0 < 0 || amount1 < 0 || (amount0 == 0 && amount1 == 0) {
+            return Err(Error::InvalidAmount);
+        }
+
+        let cfg = read_pool_config(&env)?;
+        let pool_addr = pool_address(&env);
+        let mut ps = read_pool_state(&env)?;
+        if ps.liquidity <= 0 {
+            return Err(Error::NoActiveLiquidity);
+        }
+
+        let fee_bps = cfg.fee_bps as u128;
+        let fee0 =
+            math::mul_div_ceil(&env, amount0 as u128, fee_bps, math::FEE_BPS_DENOMINATOR)? as i128;
+        let fee1 =
+            math::mul_div_ceil(&env, amount1 as u128, fee_bps, math::FEE_BPS_DENOMINATOR)? as i128;
+
+        let token0 = token::Client::new(&env, &cfg.token_a);
+        let token1 = token::Client::new(&env, &cfg.token_b);
+        let balance0_before = token0.balance(&pool_addr);
+        let balance1_before = token1.balance(&pool_addr);
+
+        if amount0 > 0 {
+            token0.transfer(&pool_addr, &recipient, &amount0);
+        }
+        if amount1 > 0 {
+            token1.transfer(&pool_addr, &recipient, &amount1);
         }
+
+        FlashCallbackClient::new(&env, &recipient).flash_callback(&fee0, &fee1, &data);
+
+        let balance0_after = token0.balance(&pool_addr);
+        let balance1_after = token1.balance(&pool_addr);
+        if balance0_after < balance0_before + fee0 || balance1_after < balance1_before + fee1 {
+            return Err(Error::InsufficientInputAmount);
+        }
+
+        // fee dibayar (bisa lebih dari minimum) → protocol + LP
+        let paid0 = balance0_after - balance0_before;
+        let paid1 = balance1_after - balance1_before;
+
+        credit_fees(&env, &mut ps, paid0, paid1)?;
+        write_pool_state(&env, &ps);
+
+        Ok((paid0, paid1))
     }
 
     // ------------------------------------
     // ADD LIQUIDITY
     // ------------------------------------
 
+    // Token yang ditarik dihitung dari liquidity + harga sekarang
+    // (math::get_amounts_for_liquidity, round up), bukan dari caller.
+    // amount0_max / amount1_max = batas slippage, deadline = ledger timestamp.
+    //
+    // Return: (amount0, amount1) yang beneran ditarik.
     pub fn add_liquidity(
         env: Env,
         owner: Address,
         lower: i32,
         upper: i32,
         liquidity: i128,
-        amt_a: i128,
-        amt_b: i128,
-    ) {
+        amount0_max: i128,
+        amount1_max: i128,
+        deadline: u64,
+    ) -> Result<(i128, i128), Error> {
         owner.require_auth();
-        if lower >= upper {
-            panic!("tick_lower must < tick_upper");
-        }
+        check_deadline(&env, deadline)?;
         if liquidity <= 0 {
-            panic!("liquidity must > 0");
+            return Err(Error::InvalidLiquidity);
         }
 
-        let cfg = read_pool_config(&env);
+        let cfg = read_pool_config(&env)?;
         let pool_addr = pool_address(&env);
+        let mut pool = read_pool_state(&env)?;
+
+        // range harus align ke tick_spacing + di dalam MIN/MAX tick
+        tick::check_ticks(lower, upper, pool.tick_spacing)?;
+
+        // hitung token yang dibutuhkan di harga sekarang
+        let (amount0, amount1) = math::get_amounts_for_liquidity(
+            &env,
+            liquidity,
+            math::tick_to_sqrt_price_x64(&env, lower)?,
+            math::tick_to_sqrt_price_x64(&env, upper)?,
+            pool.sqrt_price_x64,
+            true,
+        )?;
+
+        if amount0 > amount0_max || amount1 > amount1_max {
+            return Err(Error::SlippageExceeded);
+        }
 
         // transfer tokens
-        token::Client::new(&env, &cfg.token_a).transfer(&owner, &pool_addr, &amt_a);
-        token::Client::new(&env, &cfg.token_b).transfer(&owner, &pool_addr, &amt_b);
-
-        // update global PoolState
-        let mut ps = read_pool_state(&env);
-        ps.liquidity += liquidity;
-        write_pool_state(&env, &ps);
+        if amount0 > 0 {
+            token::Client::new(&env, &cfg.token_a).transfer(&owner, &pool_addr, &amount0);
+        }
+        if amount1 > 0 {
+            token::Client::new(&env, &cfg.token_b).transfer(&owner, &pool_addr, &amount1);
+        }
 
-        // update tick_lower
-        let mut lo = read_tick_info_persistent(&env, lower);
-        lo.liquidity_gross += liquidity;
-        lo.liquidity_net += liquidity;
-        write_tick_info_persistent(&env, lower, &lo);
+        // liquidity aktif cuma nambah kalau posisi in-range
+        if is_in_range(&pool, lower, upper) {
+            oracle::record(&env, &mut pool);
+            pool.liquidity += liquidity;
+            write_pool_state(&env, &pool);
+        }
 
-        // update tick_upper
-        let mut up = read_tick_info_persistent(&env, upper);
-        up.liquidity_gross += liquidity;
-        up.liquidity_net -= liquidity;
-        write_tick_info_persistent(&env, upper, &up);
+        // update tick_lower / tick_upper (tick.rs) + flip bitmap
+        let max_liquidity = tick::max_liquidity_per_tick(pool.tick_spacing);
+        if tick::update_tick(&env, lower, &pool, liquidity, false, max_liquidity)? {
+            tick::flip_tick(&env, lower, pool.tick_spacing);
+        }
+        if tick::update_tick(&env, upper, &pool, liquidity, true, max_liquidity)? {
+            tick::flip_tick(&env, upper, pool.tick_spacing);
+        }
 
-        // update position
+        // update position (settle fee dulu, tick baru sudah ke-init di atas)
         let mut pos = read_position(&env, &owner, lower, upper);
+        settle_position_fees(&env, &pool, &mut pos, lower, upper)?;
         pos.liquidity += liquidity;
-        pos.token_a_amount += amt_a;
-        pos.token_b_amount += amt_b;
         write_position(&env, &owner, lower, upper, &pos);
+
+        Ok((amount0, amount1))
     }
 
     // ------------------------------------
-    // REMOVE LIQUIDITY
+    // REMOVE LIQUIDITY (burn)
     // ------------------------------------
-
+    //
+    // Token yang jadi hak LP dihitung dari liquidity + harga SEKARANG
+    // (math::get_amounts_for_liquidity, round down), bukan dari rasio
+    // deposit awal. Hasilnya masuk tokens_owed, belum ditransfer —
+    // ambil lewat collect.
+    //
+    // caller = owner atau operator owner (lihat set_operator).
+    // amount0_min / amount1_min = batas slippage, deadline = ledger timestamp.
+    //
+    // Return: (amount0, amount1) yang di-credit ke tokens_owed.
     pub fn remove_liquidity(
         env: Env,
+        caller: Address,
         owner: Address,
         lower: i32,
         upper: i32,
         liquidity: i128,
-    ) {
+        amount0_min: i128,
+        amount1_min: i128,
+        deadline: u64,
+    ) -> Result<(i128, i128), Error> {
+        require_owner_or_operator(&env, &caller, &owner)?;
+        check_deadline(&env, deadline)?;
         if liquidity <= 0 {
-            panic!("liquidity must > 0");
+            return Err(Error::InvalidLiquidity);
         }
 
-        let cfg = read_pool_config(&env);
-        let pool_addr = pool_address(&env);
-
         let mut pos = read_position(&env, &owner, lower, upper);
         if pos.liquidity < liquidity {
-            panic!("not enough liquidity");
+            return Err(Error::InsufficientLiquidity);
         }
 
-        let out_a = pos.token_a_amount * liquidity / pos.liquidity;
-        let out_b = pos.token_b_amount * liquidity / pos.liquidity;
+        // settle fee sebelum liquidity posisi & tick berubah
+        let mut ps = read_pool_state(&env)?;
+        settle_position_fees(&env, &ps, &mut pos, lower, upper)?;
+
+        let (amount0, amount1) = math::get_amounts_for_liquidity(
+            &env,
+            liquidity,
+            math::tick_to_sqrt_price_x64(&env, lower)?,
+            math::tick_to_sqrt_price_x64(&env, upper)?,
+            ps.sqrt_price_x64,
+            false,
+        )?;
+        if amount0 < amount0_min || amount1 < amount1_min {
+            return Err(Error::SlippageExceeded);
+        }
 
         pos.liquidity -= liquidity;
-        pos.token_a_amount -= out_a;
-        pos.token_b_amount -= out_b;
+        pos.tokens_owed0 += amount0;
+        pos.tokens_owed1 += amount1;
         write_position(&env, &owner, lower, upper, &pos);
 
-        // global liquidity
-        let mut ps = read_pool_state(&env);
-        ps.liquidity -= liquidity;
+        // global liquidity (cuma kalau posisi in-range)
+        if is_in_range(&ps, lower, upper) {
+            oracle::record(&env, &mut ps);
+            ps.liquidity -= liquidity;
+            write_pool_state(&env, &ps);
+        }
+
+        // ticks (flip bitmap kalau liquidity_gross balik ke 0)
+        let max_liquidity = tick::max_liquidity_per_tick(ps.tick_spacing);
+        if tick::update_tick(&env, lower, &ps, -liquidity, false, max_liquidity)? {
+            tick::flip_tick(&env, lower, ps.tick_spacing);
+        }
+        if tick::update_tick(&env, upper, &ps, -liquidity, true, max_liquidity)? {
+            tick::flip_tick(&env, upper, ps.tick_spacing);
+        }
+
+        Ok((amount0, amount1))
+    }
+
+    // ------------------------------------
+    // COLLECT FEE
+    // ------------------------------------
+    //
+    // Settle fee posisi ke tokens_owed, lalu kirim maksimal
+    // amount0_max / amount1_max ke recipient. Liquidity posisi ga disentuh.
+    // tokens_owed juga berisi token hasil remove_liquidity.
+    // caller = owner atau operator owner (lihat set_operator).
+    //
+    // Return: (amount0, amount1) yang beneran dikirim.
+    pub fn collect(
+        env: Env,
+        caller: Address,
+        owner: Address,
+        lower: i32,
+        upper: i32,
+        recipient: Address,
+        amount0_max: i128,
+        amount1_max: i128,
+    ) -> Result<(i128, i128), Error> {
+        require_owner_or_operator(&env, &caller, &owner)?;
+        if amount0_max < 0 || amount1_max < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let cfg = read_pool_config(&env)?;
+        let pool_addr = pool_address(&env);
+        let ps = read_pool_state(&env)?;
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+        // posisi tanpa liquidity ga punya fee baru (tick-nya bisa sudah dihapus)
+        if pos.liquidity > 0 {
+            settle_position_fees(&env, &ps, &mut pos, lower, upper)?;
+        }
+
+        let amount0 = pos.tokens_owed0.min(amount0_max);
+        let amount1 = pos.tokens_owed1.min(amount1_max);
+
+        pos.tokens_owed0 -= amount0;
+        pos.tokens_owed1 -= amount1;
+        write_position(&env, &owner, lower, upper, &pos);
+
+        if amount0 > 0 {
+            token::Client::new(&env, &cfg.token_a).transfer(&pool_addr, &recipient, &amount0);
+        }
+        if amount1 > 0 {
+            token::Client::new(&env, &cfg.token_b).transfer(&pool_addr, &recipient, &amount1);
+        }
+
+        Ok((amount0, amount1))
+    }
+
+    // ------------------------------------
+    // OPERATOR (delegasi posisi)
+    // ------------------------------------
+    //
+    // Owner kasih / cabut izin ke operator buat remove_liquidity dan
+    // collect atas SEMUA posisi owner. Token tetap milik owner, tapi
+    // operator bebas pilih recipient waktu collect — cuma approve
+    // kontrak yang dipercaya.
+    pub fn set_operator(
+        env: Env,
+        owner: Address,
+        operator: Address,
+        approved: bool,
+    ) -> Result<(), Error> {
+        owner.require_auth();
+        if owner == operator {
+            return Err(Error::InvalidOperator);
+        }
+        write_operator(&env, &owner, &operator, approved);
+        Ok(())
+    }
+
+    pub fn is_operator(env: Env, owner: Address, operator: Address) -> bool {
+        read_operator(&env, &owner, &operator)
+    }
+
+    // ------------------------------------
+    // PROTOCOL FEE (admin)
+    // ------------------------------------
+    //
+    // fee_protocol0 / fee_protocol1 = N → protocol ambil 1/N dari fee
+    // swap arah itu. 0 = off, selain itu harus 4..=10 (25% .. 10%).
+    // Cuma berlaku untuk swap setelah diset.
+    pub fn set_fee_protocol(
+        env: Env,
+        fee_protocol0: u32,
+        fee_protocol1: u32,
+    ) -> Result<(), Error> {
+        let cfg = read_pool_config(&env)?;
+        cfg.admin.require_auth();
+
+        let valid = |n: u32| n == 0 || (4..=10).contains(&n);
+        if !valid(fee_protocol0) || !valid(fee_protocol1) {
+            return Err(Error::InvalidFeeProtocol);
+        }
+
+        let mut ps = read_pool_state(&env)?;
+        ps.fee_protocol0 = fee_protocol0;
+        ps.fee_protocol1 = fee_protocol1;
         write_pool_state(&env, &ps);
+        Ok(())
+    }
+
+    // Kirim semua protocol fee yang terkumpul ke recipient.
+    //
+    // Return: (amount0, amount1) yang dikirim.
+    pub fn collect_protocol(env: Env, recipient: Address) -> Result<(i128, i128), Error> {
+        let cfg = read_pool_config(&env)?;
+        cfg.admin.require_auth();
+
+        let pool_addr = pool_address(&env);
+        let mut ps = read_pool_state(&env)?;
 
-        // ticks
-        let mut lo = read_tick_info_persistent(&env, lower);
-        lo.liquidity_gross -= liquidity;
-        lo.liquidity_net -= liquidity;
-        write_tick_info_persistent(&env, lower, &lo);
+        let amount0 = ps.protocol_fees0;
+        let amount1 = ps.protocol_fees1;
+        ps.protocol_fees0 = 0;
+        ps.protocol_fees1 = 0;
+        write_pool_state(&env, &ps);
 
-        let mut up = read_tick_info_persistent(&env, upper);
-        up.liquidity_gross -= liquidity;
-        up.liquidity_net += liquidity;
-        write_tick_info_persistent(&env, upper, &up);
+        if amount0 > 0 {
+            token::Client::new(&env, &cfg.token_a).transfer(&pool_addr, &recipient, &amount0);
+        }
+        if amount1 > 0 {
+            token::Client::new(&env, &cfg.token_b).transfer(&pool_addr, &recipient, &amount1);
+        }
 
-        // transfer back
-        token::Client::new(&env, &cfg.token_a).transfer(&pool_addr, &owner, &out_a);
-        token::Client::new(&env, &cfg.token_b).transfer(&pool_addr, &owner, &out_b);
+        Ok((amount0, amount1))
     }
 
-}
+    // ------------------------------------
+    // TWAP ORACLE
+    // ------------------------------------
+    //
+    // Perbesar ring buffer observation (default 1 = cuma nyimpen titik
+    // terakhir). Siapa aja boleh panggil; yang manggil bayar storage.
+    // Slot baru mulai dipakai waktu index muter ke ujung buffer.
+    pub fn grow_observation_cardinality(env: Env, cardinality_next: u32) -> Result<u32, Error> {
+        let mut ps = read_pool_state(&env)?;
+        let next = oracle::grow(&env, ps.observation_cardinality_next, cardinality_next)?;
+        if next != ps.observation_cardinality_next {
+            ps.observation_cardinality_next = next;
+            write_pool_state(&env, &ps);
+        }
+        Ok(next)
+    }
+
+    // Nilai kumulatif di (sekarang - seconds_ago) untuk tiap elemen.
+    //   TWAP tick = (tick_cum[a] - tick_cum[b]) / (seconds_agos[b] - seconds_agos[a])
+    //
+    // Return: (tick_cumulatives, seconds_per_liquidity_cumulatives_x64).
+    // seconds_ago lebih tua dari observation tertua → ObservationTooOld.
+    pub fn observe(env: Env, seconds_agos: Vec<u64>) -> Result<(Vec<i64>, Vec<u128>), Error> {
+        let ps = read_pool_state(&env)?;
+        oracle::observe(&env, &ps, &seconds_agos)
+    }
 
+    pub fn get_observation(env: Env, index: u32) -> Observation {
+        oracle::read_observation(&env, index)
+    }
 
+    // Snapshot kumulatif di dalam range [lower, upper):
+    //   (tick_cumulative_inside, seconds_per_liquidity_inside_x64, seconds_inside)
+    // Bandingkan 2 snapshot (selama range tetap punya liquidity) untuk
+    // tahu berapa lama range aktif dan dengan liquidity berapa.
+    pub fn snapshot_cumulatives_inside(
+        env: Env,
+        lower: i32,
+        upper: i32,
+    ) -> Result<(i64, u128, u64), Error> {
+        let ps = read_pool_state(&env)?;
+        tick::snapshot_cumulatives_inside(&env, &ps, lower, upper)
+    }
 
+}
@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 
-use soroban_sdk::Env;
+use soroban_sdk::{Env, U256};
+
+use crate::error::Error;
+use crate::tick::{MAX_TICK, MIN_TICK};
 
 // =============================================================
 // MATH CLMM (v0) – Tick <-> sqrt_price_x64
@@ -12,39 +15,163 @@ use soroban_sdk::Env;
 //   sqrt_price_x64 = floor( sqrt_price * 2^64 )   (Q64.64)
 //
 // Di v0 ini kita:
-//  - Implement tick_to_sqrt_price_x64 (beneran matematis)
-//  - Implement sqrt_price_to_tick (approx, buat debug / view)
+//  - Implement tick_to_sqrt_price_x64 (O(1), bit-decomposition)
+//  - Implement sqrt_price_to_tick (log2, kebalikan persis tick_to_sqrt)
+//
+// Swap math (amount delta + compute_swap_step, exact in / exact out)
+// sudah beneran, intermediate-nya pakai U256 host biar ga overflow.
+//
+// Liquidity <-> amount (buat add/remove liquidity) pakai rumus yang sama,
+// dengan arah pembulatan eksplisit.
 //
-// Fungsi lain (liquidity / swap) masih dummy dulu biar kontrak
-// tetap compile dan jalan.
+// Semua fungsi publik return Result<_, Error>: overflow / input aneh
+// jadi error kontrak (MathOverflow / AmountOverflow / ...), bukan panic.
 //
 // =============================================================
 
 // 2^64 dalam u128 (basis Q64.64)
 const ONE_X64: u128 = 1u128 << 64;
 
-// sqrt(1.0001) dalam Q64.64
-// sqrt(1.0001) ≈ 1.0000499987500624
-// SQRT_1_0001_X64 = floor( sqrt(1.0001) * 2^64 )
-const SQRT_1_0001_X64: u128 = 18_447_666_387_855_958_016u128;
+// Batas sqrt_price_x64 = tick_to_sqrt_price_x64(MIN_TICK / MAX_TICK)
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_017u128;
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_062u128;
+
+// 1.0 dalam Q1.127 (basis ratio di tick_to_sqrt_price_x64)
+const ONE_X127: u128 = 1u128 << 127;
+
+// Magic constants: TICK_RATIO_X127[i] = floor( 2^127 / 1.0001^(2^i / 2) )
+// i = 0..18 cukup buat |tick| <= MAX_TICK (< 2^19)
+const TICK_RATIO_X127: [u128; 19] = [
+    0x7ffe5c99deb7d69bd5168b168d2ca000,
+    0x7ffcb9391b9ea0992cd234c82c07109c,
+    0x7ff97287afb2b49977891abe79e3fee5,
+    0x7ff2e5653f0872730e1b1275504a0e67,
+    0x7fe5cc21eb07b0ace4edac41ae493321,
+    0x7fcb9da0fd4c6040a397344b6fd92a5f,
+    0x7f9750b23364b51c21f63c59935a9430,
+    0x7f2ef702354cd15408e230f8cb4e1829,
+    0x7e5f4363c80544576e7fe41da3cd51d1,
+    0x7cc3d3929d62098bb79583a67bc0af29,
+    0x799c9584115b8002ca063d1cc725b879,
+    0x738aca3ad1614dba21d94e3fd37444ec,
+    0x684bf9defe90115c422d6c7bc9552c12,
+    0x54fba32316c387efc532ee0fc87030f2,
+    0x386c34d0ab6950dc485d9efb15d7997b,
+    0x18df09afcbe847ecc0918a82aa17e7d2,
+    0x04d52845adbd4270e33bef2a79f4cde4,
+    0x002eb57c6f6dc08cb34ce194912f7302,
+    0x0000110b72c27afd0f5493020df6ff4b,
+];
+
+// 1 / log2(sqrt(1.0001)) dalam Q32.32 (buat sqrt_price_to_tick)
+const LOG2_SQRT_1_0001_INV_X32: i128 = 59_543_866_431_248;
+
+// Jumlah bit pecahan log2 yang dihitung. 24 bit → error tick < 0.001
+const LOG2_FRAC_BITS: u32 = 24;
+
+// Toleransi estimasi tick (2^-7 tick) dalam Q64.64
+const LOG2_TICK_ERROR_X64: i128 = 1 << 57;
+
+// Fee dalam basis points (1 bps = 0.01%)
+pub const FEE_BPS_DENOMINATOR: u128 = 10_000;
+
+// -------------------------------------------------------------
+// Helper fixed-point
+// -------------------------------------------------------------
+
+// (a * b) >> shift (64 <= shift < 128), produk 256-bit dihitung manual
+// per limb 64-bit. Tanpa host call, dipakai di loop tick <-> sqrt_price.
+// Caller yang jamin hasilnya muat di u128.
+fn mul_shr(a: u128, b: u128, shift: u32) -> u128 {
+    let mask = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & mask);
+    let (b_hi, b_lo) = (b >> 64, b & mask);
+
+    let ll = a_lo * b_lo;
+    let lh = a_lo * b_hi;
+    let hl = a_hi * b_lo;
+    let hh = a_hi * b_hi;
+
+    let mid = (ll >> 64) + (lh & mask) + (hl & mask);
+    let lo = (ll & mask) | (mid << 64);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+
+    (hi << (128 - shift)) | (lo >> shift)
+}
 
 // -------------------------------------------------------------
-// Helper Q64.64
+// Helper 256-bit (U256 host)
 // -------------------------------------------------------------
+//
+// Semua perkalian yang bisa lewat 128 bit dikerjakan di U256,
+// lalu dibalikin ke u128. Kalau hasil akhirnya ga muat → MathOverflow.
+//
 
 #[inline]
-fn mul_q64(a: u128, b: u128) -> u128 {
-    // (a * b) / 2^64
-    //
-    // NOTE: untuk v0 kita pakai saturating_mul biar ga panic kalau overflow.
-    // Selama tick di sekitar 0 (range kecil) ini aman.
-    a.saturating_mul(b) / ONE_X64
+fn u256(env: &Env, x: u128) -> U256 {
+    U256::from_parts(env, 0, 0, (x >> 64) as u64, x as u64)
 }
 
 #[inline]
-fn div_q64(a: u128, b: u128) -> u128 {
-    // (a * 2^64) / b
-    a.saturating_mul(ONE_X64) / b
+fn u256_to_u128(x: &U256) -> Result<u128, Error> {
+    x.to_u128().ok_or(Error::MathOverflow)
+}
+
+// floor(a * b / denominator)
+pub fn mul_div_floor(env: &Env, a: u128, b: u128, denominator: u128) -> Result<u128, Error> {
+    if denominator == 0 {
+        return Err(Error::MathOverflow);
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return Ok(product / denominator);
+    }
+
+    u256_to_u128(&u256(env, a).mul(&u256(env, b)).div(&u256(env, denominator)))
+}
+
+// ceil(a * b / denominator)
+pub fn mul_div_ceil(env: &Env, a: u128, b: u128, denominator: u128) -> Result<u128, Error> {
+    if denominator == 0 {
+        return Err(Error::MathOverflow);
+    }
+    if let Some(product) = a.checked_mul(b) {
+        return Ok(product.div_ceil(denominator));
+    }
+
+    let product = u256(env, a).mul(&u256(env, b));
+    let d = u256(env, denominator);
+    let q = u256_to_u128(&product.div(&d))?;
+    if product.rem_euclid(&d) != U256::from_u32(env, 0) {
+        q.checked_add(1).ok_or(Error::MathOverflow)
+    } else {
+        Ok(q)
+    }
+}
+
+// (num * 2^64) / den, dengan den < 2^192.
+//
+// num * 2^64 bisa lewat 256 bit, jadi dipecah:
+//   num = q * den + r
+//   num * 2^64 / den = q * 2^64 + (r * 2^64) / den
+// r < den < 2^192 → r * 2^64 masih muat di U256.
+fn mul_shl64_div(env: &Env, num: &U256, den: &U256, round_up: bool) -> Result<u128, Error> {
+    let zero = U256::from_u32(env, 0);
+    if den.shr(192) != zero {
+        return Err(Error::MathOverflow);
+    }
+
+    let q = u256_to_u128(&num.div(den))?;
+    let r_shifted = num.rem_euclid(den).shl(64);
+    let frac = u256_to_u128(&r_shifted.div(den))?;
+
+    let mut result = q
+        .checked_mul(ONE_X64)
+        .and_then(|x| x.checked_add(frac))
+        .ok_or(Error::MathOverflow)?;
+    if round_up && r_shifted.rem_euclid(den) != zero {
+        result = result.checked_add(1).ok_or(Error::MathOverflow)?;
+    }
+    Ok(result)
 }
 
 // -------------------------------------------------------------
@@ -54,154 +181,516 @@ fn div_q64(a: u128, b: u128) -> u128 {
 // Definisi:
 //   price      = 1.0001^tick
 //   sqrt_price = 1.0001^(tick/2)
-//   sqrt_x64   = floor( sqrt_price * 2^64 )
+//   sqrt_x64   = sqrt_price * 2^64  (dibulatkan ke atas)
 //
-// Implementasi v0:
-//   - Mulai dari sqrt_price = 1.0 (ONE_X64)
-//   - Hitung faktor = (sqrt(1.0001))^|tick|
-//   - Kalau tick > 0 → sqrt = 1.0 * faktor
-//   - Kalau tick < 0 → sqrt = 1.0 / faktor
+// Implementasi O(1) (gaya TickMath Uniswap V3):
+//   - |tick| dipecah per bit, tiap bit i yang nyala dikali
+//     TICK_RATIO_X127[i] = 1 / 1.0001^(2^i / 2)  (Q1.127)
+//   - hasilnya ratio = 1.0001^(-|tick|/2), selalu <= 1 → ga overflow
+//   - tick < 0 → sqrt = ratio        (geser ke Q64.64)
+//   - tick > 0 → sqrt = 1 / ratio
 //
-// Kompleksitas: O(|tick|) → cukup buat tick dekat 0 / testnet.
+// Maksimal 19 perkalian, berlaku untuk MIN_TICK..=MAX_TICK.
+// Tick di luar range → TickOutOfRange.
 //
-pub fn tick_to_sqrt_price_x64(_env: &Env, tick: i32) -> u128 {
-    if tick == 0 {
-        return ONE_X64;
+pub fn tick_to_sqrt_price_x64(env: &Env, tick: i32) -> Result<u128, Error> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(Error::TickOutOfRange);
     }
 
-    let abs_tick = if tick < 0 { -tick } else { tick } as u32;
+    let abs_tick = tick.unsigned_abs();
 
-    // hitung faktor = (sqrt(1.0001))^abs_tick
-    let mut factor: u128 = ONE_X64;
-    for _ in 0..abs_tick {
-        factor = mul_q64(factor, SQRT_1_0001_X64);
+    let mut ratio: u128 = if abs_tick & 1 != 0 {
+        TICK_RATIO_X127[0]
+    } else {
+        ONE_X127
+    };
+    for (i, c) in TICK_RATIO_X127.iter().enumerate().skip(1) {
+        if abs_tick & (1 << i) != 0 {
+            ratio = mul_shr(ratio, *c, 127);
+        }
     }
 
     if tick > 0 {
-        // price > 1
-        factor
+        // price > 1 → ceil( 2^127 * 2^64 / ratio )
+        mul_div_ceil(env, ONE_X127, ONE_X64, ratio)
     } else {
-        // price < 1  → ambil kebalikan
-        div_q64(ONE_X64, factor)
+        // Q1.127 → Q64.64, round up
+        Ok((ratio >> 63) + if ratio & ((1u128 << 63) - 1) != 0 { 1 } else { 0 })
     }
 }
 
 // -------------------------------------------------------------
-// Convert sqrt_price_x64 → tick (versi naive / debug)
+// Convert sqrt_price_x64 → tick (log2)
 // -------------------------------------------------------------
 //
-// Cara kerja (approx):
-//  - Kalau sqrt_price_x64 > 2^64 (price > 1):
-//      mulai dari sqrt = 1.0, kalikan sqrt(1.0001) sampai >= target.
-//  - Kalau sqrt_price_x64 < 2^64 (price < 1):
-//      mulai dari sqrt = 1.0, bagi sqrt(1.0001) sampai <= target.
+// Return tick terbesar yang tick_to_sqrt_price_x64(tick) <= sqrt_price_x64.
 //
-// max_abs_tick = batas iterasi biar ga infinite loop.
+// Cara kerja:
+//  1. log2(sqrt_price) dalam Q32.32:
+//       - bagian bulat  = msb(sqrt_price_x64) - 64
+//       - bagian pecahan = kuadratkan berulang (LOG2_FRAC_BITS bit)
+//  2. tick ≈ log2(sqrt_price) / log2(sqrt(1.0001))
+//  3. error estimasi < 1 tick → cek kandidat (low, high) sekali
+//     pakai tick_to_sqrt_price_x64.
 //
-// ⚠️ Jangan pakai ini buat tick ratusan ribu, ini cuma buat:
-//  - view / debug
-//  - sync current_tick sesekali kalau perlu.
+// Valid untuk MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64, di luar itu InvalidPrice.
 //
-pub fn sqrt_price_to_tick(_env: &Env, sqrt_price_x64: u128) -> i32 {
-    if sqrt_price_x64 == ONE_X64 {
-        return 0;
+pub fn sqrt_price_to_tick(env: &Env, sqrt_price_x64: u128) -> Result<i32, Error> {
+    if !(MIN_SQRT_PRICE_X64..=MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64) {
+        return Err(Error::InvalidPrice);
     }
 
-    let max_abs_tick: i32 = 10_000; // batas aman v0
-    let mut current: u128 = ONE_X64;
-    let mut tick: i32 = 0;
+    // 1. log2 bagian bulat
+    let msb = 127 - sqrt_price_x64.leading_zeros();
+    let mut log2_x32: i128 = (msb as i128 - 64) << 32;
 
-    if sqrt_price_x64 > ONE_X64 {
-        // price > 1 → tick positif
-        while tick < max_abs_tick {
-            current = mul_q64(current, SQRT_1_0001_X64);
-            tick += 1;
+    // normalisasi ke [1, 2) dalam Q2.126 (msb <= 96, jadi geser kiri)
+    let mut r: u128 = sqrt_price_x64 << (126 - msb);
 
-            if current >= sqrt_price_x64 {
-                return tick;
-            }
+    // 1b. log2 bagian pecahan
+    let mut bit: i128 = 1 << 31;
+    for _ in 0..LOG2_FRAC_BITS {
+        r = mul_shr(r, r, 126);
+        if r >= 2u128 << 126 {
+            log2_x32 += bit;
+            r >>= 1;
         }
-        max_abs_tick
-    } else {
-        // price < 1 → tick negatif
-        while tick > -max_abs_tick {
-            current = div_q64(current, SQRT_1_0001_X64);
-            tick -= 1;
+        bit >>= 1;
+    }
 
-            if current <= sqrt_price_x64 {
-                return tick;
-            }
-        }
-        -max_abs_tick
+    // 2. tick dalam Q64.64 (log2_x32 di-truncate → estimasi selalu <= asli)
+    let tick_x64: i128 = log2_x32 * LOG2_SQRT_1_0001_INV_X32;
+
+    let tick_low = ((tick_x64 - LOG2_TICK_ERROR_X64) >> 64) as i32;
+    let tick_high = ((tick_x64 + LOG2_TICK_ERROR_X64) >> 64) as i32;
+    let tick_low = tick_low.max(MIN_TICK);
+    let tick_high = tick_high.min(MAX_TICK);
+
+    // 3. pilih kandidat
+    if tick_low == tick_high {
+        Ok(tick_low)
+    } else if tick_to_sqrt_price_x64(env, tick_high)? <= sqrt_price_x64 {
+        Ok(tick_high)
+    } else {
+        Ok(tick_low)
     }
 }
 
 // =============================================================
-// SISA FUNGSI: masih dummy dulu, supaya kontrak tetap compile
+// LIQUIDITY <-> AMOUNT (posisi LP)
 // =============================================================
+//
+// Posisi di range [sqrtL, sqrtU], harga sekarang sqrtP:
+//   sqrtP <= sqrtL → cuma token0: amount0 = L * (sqrtU - sqrtL) / (sqrtU * sqrtL)
+//   sqrtP >= sqrtU → cuma token1: amount1 = L * (sqrtU - sqrtL)
+//   di tengah      → amount0 = L * (sqrtU - sqrtP) / (sqrtU * sqrtP)
+//                    amount1 = L * (sqrtP - sqrtL)
+//
+// round_up:
+//   true  → pool NERIMA token (add liquidity)
+//   false → pool BAYAR token (remove liquidity)
+//
 
-// Hitung amount0 & amount1 untuk liquidity di range
 pub fn get_amounts_for_liquidity(
-    _env: &Env,
+    env: &Env,
     liquidity: i128,
-    _sqrt_price_lower: u128,
-    _sqrt_price_upper: u128,
-    _current_sqrt_price: u128,
-) -> (i128, i128) {
-    let _ = liquidity;
-    // TODO: nanti diisi rumus Uniswap V3:
-    // amount0 = L * (sqrtU - sqrtL) / (sqrtU * sqrtL)
-    // amount1 = L * (sqrtU - sqrtL)
-    (0, 0)
-}
-
-// Hitung liquidity berdasarkan amount0
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    current_sqrt_price: u128,
+    round_up: bool,
+) -> Result<(i128, i128), Error> {
+    if liquidity < 0 {
+        return Err(Error::InvalidLiquidity);
+    }
+    let (lower, upper) = if sqrt_price_lower < sqrt_price_upper {
+        (sqrt_price_lower, sqrt_price_upper)
+    } else {
+        (sqrt_price_upper, sqrt_price_lower)
+    };
+    let liq = liquidity as u128;
+
+    let (amount0, amount1) = if current_sqrt_price <= lower {
+        (get_amount0_delta(env, lower, upper, liq, round_up)?, 0)
+    } else if current_sqrt_price < upper {
+        (
+            get_amount0_delta(env, current_sqrt_price, upper, liq, round_up)?,
+            get_amount1_delta(env, lower, current_sqrt_price, liq, round_up)?,
+        )
+    } else {
+        (0, get_amount1_delta(env, lower, upper, liq, round_up)?)
+    };
+
+    Ok((to_amount(amount0)?, to_amount(amount1)?))
+}
+
+// Liquidity dari amount0 (round down, pool ga pernah kasih L lebih)
+//   L = amount0 * (sqrtU * sqrtL) / (sqrtU - sqrtL)
 pub fn get_liquidity_for_amount0(
-    _env: &Env,
+    env: &Env,
     amount0: i128,
-    _sqrt_price_lower: u128,
-    _sqrt_price_upper: u128,
-) -> i128 {
-    let _ = amount0;
-    // TODO: L = amount0 * (sqrtU * sqrtL) / (sqrtU - sqrtL)
-    0
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+) -> Result<i128, Error> {
+    if amount0 < 0 {
+        return Err(Error::InvalidAmount);
+    }
+    let (lower, upper) = if sqrt_price_lower < sqrt_price_upper {
+        (sqrt_price_lower, sqrt_price_upper)
+    } else {
+        (sqrt_price_upper, sqrt_price_lower)
+    };
+    if lower == upper {
+        return Err(Error::InvalidTickRange);
+    }
+
+    let intermediate = mul_div_floor(env, lower, upper, ONE_X64)?;
+    to_liquidity(mul_div_floor(env, amount0 as u128, intermediate, upper - lower)?)
 }
 
-// Hitung liquidity berdasarkan amount1
+// Liquidity dari amount1 (round down)
+//   L = amount1 / (sqrtU - sqrtL)
 pub fn get_liquidity_for_amount1(
-    _env: &Env,
+    env: &Env,
     amount1: i128,
-    _sqrt_price_lower: u128,
-    _sqrt_price_upper: u128,
-) -> i128 {
-    let _ = amount1;
-    // TODO: L = amount1 / (sqrtU - sqrtL)
-    0
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+) -> Result<i128, Error> {
+    if amount1 < 0 {
+        return Err(Error::InvalidAmount);
+    }
+    let diff = sqrt_price_lower.abs_diff(sqrt_price_upper);
+    if diff == 0 {
+        return Err(Error::InvalidTickRange);
+    }
+
+    to_liquidity(mul_div_floor(env, amount1 as u128, ONE_X64, diff)?)
+}
+
+#[inline]
+fn to_amount(x: u128) -> Result<i128, Error> {
+    i128::try_from(x).map_err(|_| Error::AmountOverflow)
 }
 
-// Core SWAP math (dummy)
+#[inline]
+fn to_liquidity(x: u128) -> Result<i128, Error> {
+    i128::try_from(x).map_err(|_| Error::MathOverflow)
+}
+
+// =============================================================
+// AMOUNT DELTA (token yang dibutuhkan untuk gerakin harga)
+// =============================================================
+//
+//   amount0 = L * (sqrtB - sqrtA) / (sqrtA * sqrtB)
+//   amount1 = L * (sqrtB - sqrtA)
 //
-// Nanti fungsi ini akan:
-// - menentukan next sqrt price
-// - menentukan amount_in/out
-// - menentukan apakah boundary tercapai
+// Semua sqrt dalam Q64.64, jadi amount0 dikali 2^64 dan amount1
+// dibagi 2^64. round_up = true kalau pool yang nerima token.
+//
+
+pub fn get_amount0_delta(
+    env: &Env,
+    sqrt_price_a: u128,
+    sqrt_price_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128, Error> {
+    let (lower, upper) = if sqrt_price_a < sqrt_price_b {
+        (sqrt_price_a, sqrt_price_b)
+    } else {
+        (sqrt_price_b, sqrt_price_a)
+    };
+    if lower == 0 {
+        return Err(Error::InvalidPrice);
+    }
+    if liquidity == 0 || lower == upper {
+        return Ok(0);
+    }
+
+    let num = u256(env, liquidity).mul(&u256(env, upper - lower));
+    let den = u256(env, lower).mul(&u256(env, upper));
+    mul_shl64_div(env, &num, &den, round_up)
+}
+
+pub fn get_amount1_delta(
+    env: &Env,
+    sqrt_price_a: u128,
+    sqrt_price_b: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128, Error> {
+    let diff = sqrt_price_a.abs_diff(sqrt_price_b);
+
+    if round_up {
+        mul_div_ceil(env, liquidity, diff, ONE_X64)
+    } else {
+        mul_div_floor(env, liquidity, diff, ONE_X64)
+    }
+}
+
+// =============================================================
+// NEXT SQRT PRICE (dari amount token masuk / keluar)
+// =============================================================
+//
+// Input (exact in):
+//   zero_for_one: token0 masuk → sqrtQ = L * sqrtP / (L + amount * sqrtP)
+//   one_for_zero: token1 masuk → sqrtQ = sqrtP + amount / L
+// Output (exact out):
+//   zero_for_one: token1 keluar → sqrtQ = sqrtP - amount / L
+//   one_for_zero: token0 keluar → sqrtQ = L * sqrtP / (L - amount * sqrtP)
+//
+// Semua dibulatkan supaya harga ga "lewat" dari yang dibayar
+// trader (token0 → round up, token1 → round down).
+//
+
+pub fn get_next_sqrt_price_from_input(
+    env: &Env,
+    sqrt_price: u128,
+    liquidity: u128,
+    amount_in: u128,
+    zero_for_one: bool,
+) -> Result<u128, Error> {
+    if sqrt_price == 0 {
+        return Err(Error::InvalidPrice);
+    }
+    if liquidity == 0 {
+        return Err(Error::InvalidLiquidity);
+    }
+
+    if zero_for_one {
+        next_sqrt_price_from_amount0_rounding_up(env, sqrt_price, liquidity, amount_in, true)
+    } else {
+        next_sqrt_price_from_amount1_rounding_down(env, sqrt_price, liquidity, amount_in, true)
+    }
+}
+
+pub fn get_next_sqrt_price_from_output(
+    env: &Env,
+    sqrt_price: u128,
+    liquidity: u128,
+    amount_out: u128,
+    zero_for_one: bool,
+) -> Result<u128, Error> {
+    if sqrt_price == 0 {
+        return Err(Error::InvalidPrice);
+    }
+    if liquidity == 0 {
+        return Err(Error::InvalidLiquidity);
+    }
+
+    if zero_for_one {
+        next_sqrt_price_from_amount1_rounding_down(env, sqrt_price, liquidity, amount_out, false)
+    } else {
+        next_sqrt_price_from_amount0_rounding_up(env, sqrt_price, liquidity, amount_out, false)
+    }
+}
+
+fn next_sqrt_price_from_amount0_rounding_up(
+    env: &Env,
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u128,
+    add: bool,
+) -> Result<u128, Error> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+
+    let numerator1 = u256(env, liquidity).shl(64);
+    let product = u256(env, amount).mul(&u256(env, sqrt_price));
+
+    if add {
+        let denominator = numerator1.add(&product);
+        if denominator.shr(192) == U256::from_u32(env, 0) {
+            // L * 2^64 * sqrtP / (L * 2^64 + amount * sqrtP)
+            let num = u256(env, liquidity).mul(&u256(env, sqrt_price));
+            return mul_shl64_div(env, &num, &denominator, true);
+        }
+
+        // fallback: L * 2^64 / (L * 2^64 / sqrtP + amount)
+        let den = numerator1.div(&u256(env, sqrt_price)).add(&u256(env, amount));
+        let q = numerator1.div(&den);
+        let rounded = if numerator1.rem_euclid(&den) != U256::from_u32(env, 0) {
+            q.add(&U256::from_u32(env, 1))
+        } else {
+            q
+        };
+        u256_to_u128(&rounded)
+    } else {
+        if product >= numerator1 {
+            return Err(Error::InsufficientLiquidity);
+        }
+        let denominator = numerator1.sub(&product);
+        let num = u256(env, liquidity).mul(&u256(env, sqrt_price));
+        mul_shl64_div(env, &num, &denominator, true)
+    }
+}
+
+fn next_sqrt_price_from_amount1_rounding_down(
+    env: &Env,
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u128,
+    add: bool,
+) -> Result<u128, Error> {
+    if add {
+        let quotient = mul_div_floor(env, amount, ONE_X64, liquidity)?;
+        sqrt_price.checked_add(quotient).ok_or(Error::MathOverflow)
+    } else {
+        let quotient = mul_div_ceil(env, amount, ONE_X64, liquidity)?;
+        if sqrt_price <= quotient {
+            return Err(Error::InsufficientLiquidity);
+        }
+        Ok(sqrt_price - quotient)
+    }
+}
+
+// =============================================================
+// Core SWAP math (1 step)
+// =============================================================
+//
+// Satu step swap dari sqrt_price_current menuju sqrt_price_target
+// (biasanya sqrt price tick initialized berikutnya, atau limit).
+//
+// Arah ditentukan dari posisi target:
+//   target <= current → zero_for_one (token0 masuk, harga turun)
+//   target >  current → one_for_zero (token1 masuk, harga naik)
+//
+// amount_remaining > 0 → exact input  (fee sudah termasuk di sini)
+// amount_remaining < 0 → exact output (|amount| = token yang mau diterima)
+//
+// Pembulatan selalu menguntungkan pool:
+//   - amount_in dibulatkan ke atas
+//   - amount_out dibulatkan ke bawah
+//   - fee dibulatkan ke atas
+//
+// Return: (sqrt_price_next, amount_in, amount_out, fee_amount)
+//   - amount_in  TIDAK termasuk fee
+//   - kalau sqrt_price_next == target → boundary tercapai
 //
 pub fn compute_swap_step(
-    _env: &Env,
+    env: &Env,
     sqrt_price_current: u128,
-    liquidity: i128,
     sqrt_price_target: u128,
-    _zero_for_one: bool,
-) -> (u128, i128, i128, bool) {
-    let _ = (liquidity, sqrt_price_target);
+    liquidity: i128,
+    amount_remaining: i128,
+    fee_bps: u32,
+) -> Result<(u128, i128, i128, i128), Error> {
+    if liquidity < 0 {
+        return Err(Error::InvalidLiquidity);
+    }
+    if amount_remaining == 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    let zero_for_one = sqrt_price_current >= sqrt_price_target;
+    let exact_in = amount_remaining > 0;
+    let liq = liquidity as u128;
+    let fee = fee_bps as u128;
+    let remaining = amount_remaining.unsigned_abs();
+
+    let sqrt_price_next = if exact_in {
+        // input maksimal yang bisa dipakai untuk harga (setelah fee)
+        let remaining_less_fee = mul_div_floor(
+            env,
+            remaining,
+            FEE_BPS_DENOMINATOR - fee,
+            FEE_BPS_DENOMINATOR,
+        )?;
 
-    // dummy: harga tidak bergerak, amount = 1
-    let next_price = sqrt_price_current;
-    let amount_in = 1;
-    let amount_out = 1;
-    let reached_boundary = true;
+        let amount_in_to_target = if zero_for_one {
+            get_amount0_delta(env, sqrt_price_target, sqrt_price_current, liq, true)?
+        } else {
+            get_amount1_delta(env, sqrt_price_current, sqrt_price_target, liq, true)?
+        };
+
+        if remaining_less_fee >= amount_in_to_target {
+            sqrt_price_target
+        } else {
+            get_next_sqrt_price_from_input(
+                env,
+                sqrt_price_current,
+                liq,
+                remaining_less_fee,
+                zero_for_one,
+            )?
+        }
+    } else {
+        let amount_out_to_target = if zero_for_one {
+            get_amount1_delta(env, sqrt_price_target, sqrt_price_current, liq, false)?
+        } else {
+            get_amount0_delta(env, sqrt_price_current, sqrt_price_target, liq, false)?
+        };
+
+        if remaining >= amount_out_to_target {
+            sqrt_price_target
+        } else {
+            get_next_sqrt_price_from_output(
+                env,
+                sqrt_price_current,
+                liq,
+                remaining,
+                zero_for_one,
+            )?
+        }
+    };
 
-    (next_price, amount_in, amount_out, reached_boundary)
+    let reached_target = sqrt_price_next == sqrt_price_target;
+
+    let (amount_in, mut amount_out) = if zero_for_one {
+        (
+            get_amount0_delta(env, sqrt_price_next, sqrt_price_current, liq, true)?,
+            get_amount1_delta(env, sqrt_price_next, sqrt_price_current, liq, false)?,
+        )
+    } else {
+        (
+            get_amount1_delta(env, sqrt_price_current, sqrt_price_next, liq, true)?,
+            get_amount0_delta(env, sqrt_price_current, sqrt_price_next, liq, false)?,
+        )
+    };
+
+    // exact output: jangan kasih lebih dari yang diminta
+    if !exact_in && amount_out > remaining {
+        amount_out = remaining;
+    }
+
+    // exact input & target belum kecapai → semua sisa input = fee
+    let fee_amount = if exact_in && !reached_target {
+        remaining - amount_in
+    } else {
+        mul_div_ceil(env, amount_in, fee, FEE_BPS_DENOMINATOR - fee)?
+    };
+
+    Ok((
+        sqrt_price_next,
+        amount_in as i128,
+        amount_out as i128,
+        fee_amount as i128,
+    ))
+}
+
+// =============================================================
+// FEE GROWTH (Q64.64 per unit liquidity)
+// =============================================================
+//
+//   growth  = fee * 2^64 / L          (swap step → fee_growth_global)
+//   owed    = Δgrowth * L / 2^64      (posisi → tokens_owed)
+//
+// Dua-duanya round down, sisa pembulatan tetap di pool.
+//
+
+pub fn fee_growth_delta_x64(env: &Env, fee_amount: i128, liquidity: i128) -> Result<u128, Error> {
+    if fee_amount <= 0 || liquidity <= 0 {
+        return Ok(0);
+    }
+    mul_div_floor(env, fee_amount as u128, ONE_X64, liquidity as u128)
+}
+
+pub fn fees_owed(env: &Env, fee_growth_delta_x64: u128, liquidity: i128) -> Result<i128, Error> {
+    if fee_growth_delta_x64 == 0 || liquidity <= 0 {
+        return Ok(0);
+    }
+    to_amount(mul_div_floor(env, fee_growth_delta_x64, liquidity as u128, ONE_X64)?)
 }
 
 // Math utility: safe add/sub (v0 simple)
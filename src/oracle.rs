@@ -0,0 +1,328 @@
+use soroban_sdk::{contracttype, Env, Vec};
+
+use crate::error::Error;
+use crate::math::mul_div_floor;
+use crate::pool::PoolState;
+use crate::DataKey;
+
+// =============================================================
+// TWAP ORACLE (ring buffer observation, port Oracle.sol Uniswap V3)
+// =============================================================
+//
+// Tiap observation nyimpen nilai KUMULATIF sejak pool dibuat:
+//   tick_cumulative               += tick * Δt
+//   seconds_per_liquidity_cum_x64 += (Δt << 64) / max(L, 1)
+//
+// TWAP tick antara t0 dan t1 = (cum(t1) - cum(t0)) / (t1 - t0).
+// Ditulis maksimal sekali per ledger timestamp (swap / liquidity
+// pertama di ledger itu), jadi harga di tengah satu transaksi ga
+// bisa dipakai manipulasi.
+//
+// Waktu = env.ledger().timestamp() (u64, detik) → ga perlu logika
+// overflow 32-bit kayak di Solidity.
+//
+
+// Batas ring buffer (sama kayak uint16 di Uniswap)
+pub const MAX_CARDINALITY: u32 = 65_535;
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Observation {
+    pub timestamp: u64,
+    pub tick_cumulative: i64,
+    pub seconds_per_liquidity_cum_x64: u128,
+    pub initialized: bool,
+}
+
+// ------------------------------------------------------------
+// STORAGE: persistent, DataKey::Observation(index)
+// ------------------------------------------------------------
+
+pub fn read_observation(env: &Env, index: u32) -> Observation {
+    env.storage()
+        .persistent()
+        .get::<_, Observation>(&DataKey::Observation(index))
+        .unwrap_or(Observation {
+            timestamp: 0,
+            tick_cumulative: 0,
+            seconds_per_liquidity_cum_x64: 0,
+            initialized: false,
+        })
+}
+
+fn write_observation(env: &Env, index: u32, obs: &Observation) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Observation(index), obs);
+}
+
+// Observation baru dari observation terakhir + tick/liquidity yang
+// berlaku sejak itu
+fn transform(last: &Observation, time: u64, tick: i32, liquidity: i128) -> Observation {
+    let delta = time - last.timestamp;
+    let liq = if liquidity > 0 { liquidity as u128 } else { 1 };
+
+    Observation {
+        timestamp: time,
+        tick_cumulative: last
+            .tick_cumulative
+            .wrapping_add((tick as i64).wrapping_mul(delta as i64)),
+        seconds_per_liquidity_cum_x64: last
+            .seconds_per_liquidity_cum_x64
+            .wrapping_add(((delta as u128) << 64) / liq),
+        initialized: true,
+    }
+}
+
+// ------------------------------------------------------------
+// initialize / write / grow
+// ------------------------------------------------------------
+
+// Observation pertama waktu pool dibuat.
+// Return (cardinality, cardinality_next).
+pub fn initialize(env: &Env, time: u64) -> (u32, u32) {
+    write_observation(
+        env,
+        0,
+        &Observation {
+            timestamp: time,
+            tick_cumulative: 0,
+            seconds_per_liquidity_cum_x64: 0,
+            initialized: true,
+        },
+    );
+    (1, 1)
+}
+
+// Tulis observation baru (tick & liquidity SEBELUM berubah).
+// Kalau di timestamp ini sudah ada observation → no-op.
+// Slot baru dari grow baru dipakai waktu index muter ke ujung buffer.
+//
+// Return (index, cardinality) yang baru.
+pub fn write(
+    env: &Env,
+    index: u32,
+    time: u64,
+    tick: i32,
+    liquidity: i128,
+    cardinality: u32,
+    cardinality_next: u32,
+) -> (u32, u32) {
+    let last = read_observation(env, index);
+    if last.timestamp == time {
+        return (index, cardinality);
+    }
+
+    let cardinality_updated = if cardinality_next > cardinality && index == cardinality - 1 {
+        cardinality_next
+    } else {
+        cardinality
+    };
+
+    let index_updated = (index + 1) % cardinality_updated;
+    write_observation(env, index_updated, &transform(&last, time, tick, liquidity));
+    (index_updated, cardinality_updated)
+}
+
+// Helper buat swap / add / remove liquidity: catat state pool
+// sekarang ke oracle, update index + cardinality di PoolState
+// (caller yang nulis PoolState).
+pub fn record(env: &Env, pool: &mut PoolState) {
+    let (index, cardinality) = write(
+        env,
+        pool.observation_index,
+        env.ledger().timestamp(),
+        pool.current_tick,
+        pool.liquidity,
+        pool.observation_cardinality,
+        pool.observation_cardinality_next,
+    );
+    pool.observation_index = index;
+    pool.observation_cardinality = cardinality;
+}
+
+// Siapin slot [current, next) di storage (belum initialized).
+// Return cardinality_next yang baru.
+pub fn grow(env: &Env, current: u32, next: u32) -> Result<u32, Error> {
+    if current == 0 {
+        return Err(Error::NotInitialized);
+    }
+    if next > MAX_CARDINALITY {
+        return Err(Error::InvalidCardinality);
+    }
+    if next <= current {
+        return Ok(current);
+    }
+
+    for i in current..next {
+        write_observation(
+            env,
+            i,
+            &Observation {
+                timestamp: 0,
+                tick_cumulative: 0,
+                seconds_per_liquidity_cum_x64: 0,
+                initialized: false,
+            },
+        );
+    }
+    Ok(next)
+}
+
+// ------------------------------------------------------------
+// observe
+// ------------------------------------------------------------
+
+// Cari 2 observation yang mengapit target (binary search di ring
+// buffer). Dipanggil cuma kalau target ada di antara observation
+// tertua dan terbaru.
+fn binary_search(
+    env: &Env,
+    target: u64,
+    index: u32,
+    cardinality: u32,
+) -> (Observation, Observation) {
+    // l = observation tertua, r = terbaru (index "di-unroll")
+    let mut l = index + 1;
+    let mut r = index + cardinality;
+
+    loop {
+        let i = (l + r) / 2;
+
+        let before = read_observation(env, i % cardinality);
+        // slot belum pernah ditulis → observation tertua ada di kanan
+        if !before.initialized {
+            l = i + 1;
+            continue;
+        }
+
+        let at_or_after = read_observation(env, (i + 1) % cardinality);
+        let target_at_or_after = before.timestamp <= target;
+
+        if target_at_or_after && target <= at_or_after.timestamp {
+            return (before, at_or_after);
+        }
+
+        if !target_at_or_after {
+            r = i - 1;
+        } else {
+            l = i + 1;
+        }
+    }
+}
+
+fn get_surrounding_observations(
+    env: &Env,
+    target: u64,
+    tick: i32,
+    index: u32,
+    liquidity: i128,
+    cardinality: u32,
+) -> Result<(Observation, Observation), Error> {
+    // 1. target >= observation terbaru → extrapolate dari state sekarang
+    let newest = read_observation(env, index);
+    if newest.timestamp <= target {
+        if newest.timestamp == target {
+            return Ok((newest.clone(), newest));
+        }
+        let at = transform(&newest, target, tick, liquidity);
+        return Ok((newest, at));
+    }
+
+    // 2. observation tertua (slot setelah index, atau 0 kalau belum penuh)
+    let mut oldest = read_observation(env, (index + 1) % cardinality);
+    if !oldest.initialized {
+        oldest = read_observation(env, 0);
+    }
+    if oldest.timestamp > target {
+        return Err(Error::ObservationTooOld);
+    }
+
+    Ok(binary_search(env, target, index, cardinality))
+}
+
+// (tick_cumulative, seconds_per_liquidity_cum_x64) di
+// (time - seconds_ago). Interpolasi linear kalau jatuh di antara 2
+// observation.
+pub fn observe_single(
+    env: &Env,
+    time: u64,
+    seconds_ago: u64,
+    tick: i32,
+    index: u32,
+    liquidity: i128,
+    cardinality: u32,
+) -> Result<(i64, u128), Error> {
+    if seconds_ago == 0 {
+        let mut last = read_observation(env, index);
+        if last.timestamp != time {
+            last = transform(&last, time, tick, liquidity);
+        }
+        return Ok((last.tick_cumulative, last.seconds_per_liquidity_cum_x64));
+    }
+
+    if seconds_ago > time {
+        return Err(Error::ObservationTooOld);
+    }
+    let target = time - seconds_ago;
+
+    let (before, after) =
+        get_surrounding_observations(env, target, tick, index, liquidity, cardinality)?;
+
+    if target == before.timestamp {
+        Ok((before.tick_cumulative, before.seconds_per_liquidity_cum_x64))
+    } else if target == after.timestamp {
+        Ok((after.tick_cumulative, after.seconds_per_liquidity_cum_x64))
+    } else {
+        // di tengah
+        let observation_time_delta = after.timestamp - before.timestamp;
+        let target_delta = target - before.timestamp;
+
+        let tick_cumulative = before.tick_cumulative.wrapping_add(
+            (after.tick_cumulative.wrapping_sub(before.tick_cumulative)
+                / observation_time_delta as i64)
+                .wrapping_mul(target_delta as i64),
+        );
+        let seconds_per_liquidity = before.seconds_per_liquidity_cum_x64.wrapping_add(
+            mul_div_floor(
+                env,
+                after
+                    .seconds_per_liquidity_cum_x64
+                    .wrapping_sub(before.seconds_per_liquidity_cum_x64),
+                target_delta as u128,
+                observation_time_delta as u128,
+            )?,
+        );
+        Ok((tick_cumulative, seconds_per_liquidity))
+    }
+}
+
+pub fn observe(
+    env: &Env,
+    pool: &PoolState,
+    seconds_agos: &Vec<u64>,
+) -> Result<(Vec<i64>, Vec<u128>), Error> {
+    if pool.observation_cardinality == 0 {
+        return Err(Error::NotInitialized);
+    }
+
+    let time = env.ledger().timestamp();
+    let mut tick_cumulatives = Vec::new(env);
+    let mut seconds_per_liquidity_cumulatives = Vec::new(env);
+
+    for seconds_ago in seconds_agos.iter() {
+        let (tc, spl) = observe_single(
+            env,
+            time,
+            seconds_ago,
+            pool.current_tick,
+            pool.observation_index,
+            pool.liquidity,
+            pool.observation_cardinality,
+        )?;
+        tick_cumulatives.push_back(tc);
+        seconds_per_liquidity_cumulatives.push_back(spl);
+    }
+
+    Ok((tick_cumulatives, seconds_per_liquidity_cumulatives))
+}
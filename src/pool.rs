@@ -1,5 +1,7 @@
-use soroban_sdk::{Env, Symbol, contracttype, Address};
+use soroban_sdk::{Env, contractevent, contracttype, Address};
 
+use crate::error::Error;
+use crate::oracle;
 use crate::DataKey;
 
 //
@@ -14,16 +16,41 @@ pub struct PoolState {
     pub tick_spacing: i32,
     pub token0: Address,
     pub token1: Address,
+    // total fee per unit liquidity aktif sejak pool dibuat (Q64.64, wrapping)
+    pub fee_growth_global0_x64: u128,
+    pub fee_growth_global1_x64: u128,
+    // protocol fee = 1/N dari fee swap per arah (0 = off, N = 4..=10)
+    //   fee_protocol0 → swap zero_for_one (fee token0)
+    //   fee_protocol1 → swap one_for_zero (fee token1)
+    pub fee_protocol0: u32,
+    pub fee_protocol1: u32,
+    // protocol fee yang belum diambil admin (collect_protocol)
+    pub protocol_fees0: i128,
+    pub protocol_fees1: i128,
+    // TWAP oracle (oracle.rs): slot terakhir yang ditulis, jumlah slot
+    // aktif, dan target jumlah slot setelah grow_observation_cardinality
+    pub observation_index: u32,
+    pub observation_cardinality: u32,
+    pub observation_cardinality_next: u32,
+}
+
+// Event init: topics ("init_pool",), data [sqrt_price, tick, spacing]
+#[contractevent(topics = ["init_pool"], data_format = "vec")]
+#[derive(Clone, Debug)]
+pub struct InitPoolEvent {
+    pub sqrt_price_x64: u128,
+    pub initial_tick: i32,
+    pub tick_spacing: i32,
 }
 
 // ------------------------------------------------------------
 // STORAGE: pakai persistent + DataKey::PoolState
 // ------------------------------------------------------------
-pub fn get_pool_state(env: &Env) -> PoolState {
+pub fn get_pool_state(env: &Env) -> Result<PoolState, Error> {
     env.storage()
         .persistent()
         .get::<_, PoolState>(&DataKey::PoolState)
-        .expect("pool not initialized")
+        .ok_or(Error::NotInitialized)
 }
 
 pub fn set_pool_state(env: &Env, state: &PoolState) {
@@ -37,7 +64,8 @@ pub fn set_pool_state(env: &Env, state: &PoolState) {
 // ------------------------------------------------------------
 //
 // NOTE:
-// - auth admin sudah di-handle di lib.rs (owner.require_auth())
+// - auth admin sudah di-handle di lib.rs (admin.require_auth())
+// - token0 < token1 sudah divalidasi di lib.rs
 // - di sini fokus set state awal + event
 //
 pub fn init_pool(
@@ -47,11 +75,13 @@ pub fn init_pool(
     tick_spacing: i32,
     token0: Address,
     token1: Address,
-) {
+) -> Result<(), Error> {
     if tick_spacing <= 0 {
-        panic!("tick_spacing must be > 0");
+        return Err(Error::InvalidTickSpacing);
     }
 
+    let (cardinality, cardinality_next) = oracle::initialize(env, env.ledger().timestamp());
+
     let state = PoolState {
         sqrt_price_x64,
         current_tick: initial_tick,
@@ -59,14 +89,27 @@ pub fn init_pool(
         tick_spacing,
         token0,
         token1,
+        fee_growth_global0_x64: 0,
+        fee_growth_global1_x64: 0,
+        fee_protocol0: 0,
+        fee_protocol1: 0,
+        protocol_fees0: 0,
+        protocol_fees1: 0,
+        observation_index: 0,
+        observation_cardinality: cardinality,
+        observation_cardinality_next: cardinality_next,
     };
 
     set_pool_state(env, &state);
 
     // EVENT
-    env.events().publish(
-        (Symbol::new(env, "init_pool"),),
-        (sqrt_price_x64, initial_tick, tick_spacing),
-    );
+    InitPoolEvent {
+        sqrt_price_x64,
+        initial_tick,
+        tick_spacing,
+    }
+    .publish(env);
+
+    Ok(())
 }
 
@@ -1,7 +1,13 @@
-use soroban_sdk::{contracttype, Address, Env, Symbol};
+use soroban_sdk::{contractevent, contracttype, Address, Env};
 
-use crate::pool::{get_pool_state, set_pool_state, PoolState};
-use crate::tick::{find_next_initialized_tick, read_tick_info, write_tick_info};
+use crate::error::Error;
+use crate::oracle;
+use crate::math::{
+    compute_swap_step, fee_growth_delta_x64, sqrt_price_to_tick, tick_to_sqrt_price_x64,
+    MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64,
+};
+use crate::pool::{get_pool_state, set_pool_state};
+use crate::tick::{cross_tick, find_next_initialized_tick, MAX_TICK, MIN_TICK};
 
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -11,81 +17,74 @@ pub struct SwapResult {
     pub sqrt_price_x64: u128,
     pub current_tick: i32,
 }
-pub fn apply_simple_price_move(
-    pool: &mut PoolState,
-    amount_in: i128,
-    zero_for_one: bool,
-) {
-    // Representasi 1.0 di Q64
-    let one_x64: u128 = 1u128 << 64;
-
-    // liquidity selalu > 0 di level call (sudah dicek di lib.rs), tapi kita tetap safe
-    let liq_u: u128 = if pool.liquidity < 0 {
-        (-pool.liquidity) as u128
-    } else {
-        pool.liquidity as u128
-    };
-
-    if liq_u == 0 {
-        // Tidak ada liquidity, tidak usah gerakin harga
-        return;
-    }
-
-    let abs_in: u128 = if amount_in < 0 {
-        (-amount_in) as u128
-    } else {
-        amount_in as u128
-    };
-
-    // seberapa besar trade relatif ke liquidity (Q64.64)
-    let trade_ratio_x64: u128 = abs_in
-        .saturating_mul(one_x64)
-        / liq_u;
-
-    // Clamp maksimal 10% (0.1 * ONE_X64)
-    let max_ratio_x64: u128 = one_x64 / 10;
-    let r: u128 = if trade_ratio_x64 > max_ratio_x64 {
-        max_ratio_x64
-    } else {
-        trade_ratio_x64
-    };
-
-    // delta_sqrt ~ perubahan sqrt_price_x64
-    // kalau trade_ratio = 10%, delta_sqrt ~ 5% dari harga sekarang
-    let mut delta_sqrt: u128 = pool
-        .sqrt_price_x64
-        .saturating_mul(r)
-        / (20 * one_x64); // 20 → max ~5%
-
-    if delta_sqrt == 0 {
-        delta_sqrt = 1; // minimal 1 biar keliatan gerak
-    }
 
-    // Arah:
-    // zero_for_one = user jual token0 (XLM) → beli token1 (USDC) → harga token1 naik (sqrt_price naik)
-    if zero_for_one {
-        pool.sqrt_price_x64 = pool.sqrt_price_x64.saturating_add(delta_sqrt);
-    } else {
-        pool.sqrt_price_x64 = pool.sqrt_price_x64.saturating_sub(delta_sqrt);
-    }
-
-    // Phase ini belum ubah current_tick sama sekali
+// Event swap: topics ("swap", caller), data [in, out, tick, sqrt_price]
+#[contractevent(topics = ["swap"], data_format = "vec")]
+#[derive(Clone, Debug)]
+pub struct SwapEvent {
+    #[topic]
+    pub caller: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
+    pub current_tick: i32,
+    pub sqrt_price_x64: u128,
 }
+
 // ENTRY INTERNAL – BUKAN #[contractimpl]
+//
+// Swap loop CLMM:
+//   amount_specified > 0 → exact input  (amount = token masuk, incl. fee)
+//   amount_specified < 0 → exact output (amount = -token keluar)
+//
+//  1. cari tick initialized berikutnya searah swap (tick bitmap,
+//     maksimal 1 word per step)
+//  2. compute_swap_step dari sqrt_price sekarang → sqrt price tick itu
+//     (atau sqrt_price_limit_x64 kalau lebih dekat)
+//  3. fee step: 1/fee_protocol ke protocol_fees, sisanya dibagi ke
+//     liquidity aktif → fee_growth_global token input
+//  4. kalau tick kecapai → cross_tick, update liquidity aktif
+//  5. ulang sampai amount habis / limit kecapai
+//
+// Exact output: amount_remaining negatif, naik ke 0 tiap dapet
+// amount_out; amount_calculated = total input (incl. fee) yang harus
+// dibayar caller.
+//
+// sqrt_price_limit_x64 = harga terjauh yang boleh dicapai. Kalau
+// kecapai sebelum amount habis → partial fill, amount_in / amount_out di
+// SwapResult cuma yang beneran terjadi. Limit wajib di sisi yang benar dari harga
+// sekarang dan di dalam (MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64).
+//
+// Fungsi ini cuma update PoolState; transfer token di lib.rs.
+// amount_in di SwapResult sudah termasuk fee.
+//
 pub fn swap(
-    env: Env,
+    env: &Env,
     caller: Address,
     amount_specified: i128,
     zero_for_one: bool,
     sqrt_price_limit_x64: u128,
-) -> SwapResult {
+    fee_bps: u32,
+) -> Result<SwapResult, Error> {
     // 1. Load PoolState
-    let mut pool = get_pool_state(&env);
+    let mut pool = get_pool_state(env)?;
+
+    if amount_specified == 0 {
+        return Err(Error::InvalidAmount);
+    }
+    let exact_input = amount_specified > 0;
 
-    if amount_specified <= 0 {
-        panic!("amount must be > 0");
+    let limit_ok = if zero_for_one {
+        sqrt_price_limit_x64 < pool.sqrt_price_x64 && sqrt_price_limit_x64 > MIN_SQRT_PRICE_X64
+    } else {
+        sqrt_price_limit_x64 > pool.sqrt_price_x64 && sqrt_price_limit_x64 < MAX_SQRT_PRICE_X64
+    };
+    if !limit_ok {
+        return Err(Error::InvalidPriceLimit);
     }
 
+    // oracle: catat tick & liquidity SEBELUM swap (sekali per ledger)
+    oracle::record(env, &mut pool);
+
     // 2. Vars lokal
     let mut amount_remaining = amount_specified;
     let mut amount_calculated: i128 = 0;
@@ -93,98 +92,150 @@ pub fn swap(
     let mut sqrt_price: u128 = pool.sqrt_price_x64;
     let mut liquidity: i128 = pool.liquidity;
     let mut current_tick: i32 = pool.current_tick;
+    let mut fee_growth_global0_x64: u128 = pool.fee_growth_global0_x64;
+    let mut fee_growth_global1_x64: u128 = pool.fee_growth_global1_x64;
+    let mut protocol_fee: i128 = 0;
+
+    // cumulative oracle di awal swap, baru dihitung waktu cross pertama
+    let time = env.ledger().timestamp();
+    let mut cumulatives: Option<(i64, u128)> = None;
 
-    // 3. Dummy loop swap
-    loop {
-        let next_initialized_tick = find_next_initialized_tick(
-            &env,
+    let fee_protocol = if zero_for_one {
+        pool.fee_protocol0
+    } else {
+        pool.fee_protocol1
+    };
+
+    // 3. Loop per tick
+    while amount_remaining != 0 && sqrt_price != sqrt_price_limit_x64 {
+        let (mut tick_next, initialized) = find_next_initialized_tick(
+            env,
             current_tick,
             pool.tick_spacing,
             zero_for_one,
         );
-
-        let (next_sqrt_price, step_in, step_out, reached_boundary) =
-            compute_swap_step_dummy(
-                sqrt_price,
-                liquidity,
-                next_initialized_tick,
-                zero_for_one,
-            );
-
-        amount_remaining -= step_in;
-        amount_calculated += step_out;
-
-        sqrt_price = next_sqrt_price;
-
-        if reached_boundary {
-            cross_tick(&env, next_initialized_tick, &mut pool, zero_for_one);
-
-            // sinkronkan local state dengan pool global
-            current_tick = pool.current_tick;
-            liquidity = pool.liquidity;
+        tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+
+        let sqrt_price_next_tick = tick_to_sqrt_price_x64(env, tick_next)?;
+        let step_start_sqrt_price = sqrt_price;
+
+        // target step = tick berikutnya, tapi jangan lewat limit
+        let sqrt_price_target = if zero_for_one {
+            sqrt_price_next_tick.max(sqrt_price_limit_x64)
+        } else {
+            sqrt_price_next_tick.min(sqrt_price_limit_x64)
+        };
+
+        let (next_sqrt_price, step_in, step_out, step_fee) = compute_swap_step(
+            env,
+            sqrt_price,
+            sqrt_price_target,
+            liquidity,
+            amount_remaining,
+            fee_bps,
+        )?;
+
+        if exact_input {
+            amount_remaining -= step_in + step_fee;
+            amount_calculated += step_out;
+        } else {
+            amount_remaining += step_out;
+            amount_calculated += step_in + step_fee;
         }
 
-        if amount_remaining <= 0 {
-            break;
+        // fee masuk di token input, potong bagian protocol dulu
+        let mut lp_fee = step_fee;
+        if fee_protocol > 0 {
+            let delta = step_fee / fee_protocol as i128;
+            lp_fee -= delta;
+            protocol_fee += delta;
         }
 
-        if zero_for_one && sqrt_price <= sqrt_price_limit_x64 {
-            break;
+        let growth = fee_growth_delta_x64(env, lp_fee, liquidity)?;
+        if zero_for_one {
+            fee_growth_global0_x64 = fee_growth_global0_x64.wrapping_add(growth);
+        } else {
+            fee_growth_global1_x64 = fee_growth_global1_x64.wrapping_add(growth);
         }
-        if !zero_for_one && sqrt_price >= sqrt_price_limit_x64 {
-            break;
+
+        sqrt_price = next_sqrt_price;
+
+        if sqrt_price == sqrt_price_next_tick {
+            // harga nyampe di tick → cross (kalau initialized)
+            if initialized {
+                let (tick_cumulative, seconds_per_liquidity_x64) = match cumulatives {
+                    Some(c) => c,
+                    None => {
+                        let c = oracle::observe_single(
+                            env,
+                            time,
+                            0,
+                            pool.current_tick,
+                            pool.observation_index,
+                            pool.liquidity,
+                            pool.observation_cardinality,
+                        )?;
+                        cumulatives = Some(c);
+                        c
+                    }
+                };
+
+                let liquidity_net = cross_tick(
+                    env,
+                    tick_next,
+                    fee_growth_global0_x64,
+                    fee_growth_global1_x64,
+                    tick_cumulative,
+                    seconds_per_liquidity_x64,
+                    time,
+                );
+                if zero_for_one {
+                    liquidity -= liquidity_net;
+                } else {
+                    liquidity += liquidity_net;
+                }
+            }
+            current_tick = if zero_for_one { tick_next - 1 } else { tick_next };
+        } else if sqrt_price != step_start_sqrt_price {
+            // berhenti di tengah range → hitung ulang tick dari harga.
+            // Kalau harga ga gerak (sisa input habis jadi fee), tick
+            // dibiarkan: bisa jadi harga persis di tick yang baru di-cross.
+            current_tick = sqrt_price_to_tick(env, sqrt_price)?;
         }
     }
 
     pool.sqrt_price_x64 = sqrt_price;
     pool.current_tick = current_tick;
     pool.liquidity = liquidity;
-
-    set_pool_state(&env, &pool);
-
-    env.events().publish(
-        (Symbol::new(&env, "swap"), caller.clone()),
-        (amount_specified, amount_calculated, current_tick, sqrt_price),
-    );
-
-    SwapResult {
-        amount_in: amount_specified - amount_remaining,
-        amount_out: amount_calculated,
-        sqrt_price_x64: sqrt_price,
-        current_tick,
-    }
-}
-
-// =============================================================
-//  DUMMY IMPLEMENTATION (kompile aman v0)
-// =============================================================
-
-fn compute_swap_step_dummy(
-    sqrt_price: u128,
-    _liquidity: i128,
-    _next_tick: i32,
-    _zero_for_one: bool,
-) -> (u128, i128, i128, bool) {
-    let next_price = sqrt_price;
-    let amount_in: i128 = 1;
-    let amount_out: i128 = 1;
-    let hit_boundary = true;
-
-    (next_price, amount_in, amount_out, hit_boundary)
-}
-
-fn cross_tick(env: &Env, tick: i32, pool: &mut PoolState, zero_for_one: bool) {
-    let info = read_tick_info(env, tick);
-
+    pool.fee_growth_global0_x64 = fee_growth_global0_x64;
+    pool.fee_growth_global1_x64 = fee_growth_global1_x64;
     if zero_for_one {
-        pool.liquidity -= info.liquidity_net;
+        pool.protocol_fees0 += protocol_fee;
     } else {
-        pool.liquidity += info.liquidity_net;
+        pool.protocol_fees1 += protocol_fee;
     }
 
-    pool.current_tick = tick;
-    write_tick_info(env, tick, &info);
-}
+    set_pool_state(env, &pool);
 
+    let (amount_in, amount_out) = if exact_input {
+        (amount_specified - amount_remaining, amount_calculated)
+    } else {
+        (amount_calculated, amount_remaining - amount_specified)
+    };
 
+    SwapEvent {
+        caller,
+        amount_in,
+        amount_out,
+        current_tick,
+        sqrt_price_x64: sqrt_price,
+    }
+    .publish(env);
 
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        sqrt_price_x64: sqrt_price,
+        current_tick,
+    })
+}
@@ -1,63 +1,379 @@
-use soroban_sdk::{Env, contracttype};
+use soroban_sdk::{Env, contracttype, panic_with_error};
+
+use crate::error::Error;
+use crate::oracle;
+use crate::pool::PoolState;
+use crate::DataKey;
 
 pub type Tick = i32;
 
+// Range tick global (sqrt_price masih muat di Q64.64)
+pub const MIN_TICK: Tick = -443_636;
+pub const MAX_TICK: Tick = 443_636;
+
+// ------------------------------------------------------------
+// Validasi range posisi
+// ------------------------------------------------------------
+//
+// lower < upper, dua-duanya kelipatan tick_spacing dan di dalam
+// MIN_TICK..=MAX_TICK.
+//
+pub fn check_ticks(lower: Tick, upper: Tick, tick_spacing: i32) -> Result<(), Error> {
+    if lower >= upper {
+        return Err(Error::InvalidTickRange);
+    }
+    if lower < MIN_TICK || upper > MAX_TICK {
+        return Err(Error::TickOutOfRange);
+    }
+    if lower % tick_spacing != 0 || upper % tick_spacing != 0 {
+        return Err(Error::TickNotAligned);
+    }
+    Ok(())
+}
+
+// Batas liquidity_gross per tick, supaya kalau SEMUA tick yang valid
+// terisi penuh, jumlah liquidity_net yang ke-cross tetap muat di i128.
+//   num_ticks = jumlah tick kelipatan tick_spacing di MIN_TICK..=MAX_TICK
+//   max       = i128::MAX / num_ticks
+pub fn max_liquidity_per_tick(tick_spacing: i32) -> i128 {
+    let min_tick = (MIN_TICK / tick_spacing) * tick_spacing;
+    let max_tick = (MAX_TICK / tick_spacing) * tick_spacing;
+    let num_ticks = ((max_tick - min_tick) / tick_spacing) as i128 + 1;
+    i128::MAX / num_ticks
+}
+
+// *_outside = nilai di sisi "luar" tick relatif ke current_tick
+// (di bawah kalau current_tick >= tick, di atas kalau belum).
+// Semua di-flip tiap cross_tick:
+//   fee_growth_outside*_x64     → fee growth (Q64.64 per unit liquidity)
+//   tick_cumulative_outside     → tick * detik (oracle)
+//   seconds_per_liq_outside_x64 → detik / liquidity (Q64.64, oracle)
+//   seconds_outside             → detik
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct TickInfo {
     pub liquidity_gross: i128,
     pub liquidity_net: i128,
+    pub fee_growth_outside0_x64: u128,
+    pub fee_growth_outside1_x64: u128,
+    pub tick_cumulative_outside: i64,
+    pub seconds_per_liq_outside_x64: u128,
+    pub seconds_outside: u64,
 }
 
 // --- helper baca tulis tick ke storage ---
+//
+// SATU-SATUNYA tempat simpan tick: persistent + DataKey::Tick(tick).
+// Dipakai add/remove liquidity (update_tick) dan swap loop (cross_tick).
 
 pub fn read_tick_info(env: &Env, tick: Tick) -> TickInfo {
     env.storage()
-        .instance()
-        .get::<Tick, TickInfo>(&tick)
+        .persistent()
+        .get::<_, TickInfo>(&DataKey::Tick(tick))
         .unwrap_or(TickInfo {
             liquidity_gross: 0,
             liquidity_net: 0,
+            fee_growth_outside0_x64: 0,
+            fee_growth_outside1_x64: 0,
+            tick_cumulative_outside: 0,
+            seconds_per_liq_outside_x64: 0,
+            seconds_outside: 0,
         })
 }
 
 pub fn write_tick_info(env: &Env, tick: Tick, info: &TickInfo) {
-    env.storage().instance().set(&tick, info);
+    env.storage().persistent().set(&DataKey::Tick(tick), info);
+}
+
+fn clear_tick_info(env: &Env, tick: Tick) {
+    env.storage().persistent().remove(&DataKey::Tick(tick));
+}
+
+// ------------------------------------------------------------
+// TICK BITMAP
+// ------------------------------------------------------------
+//
+// Tick yang initialized (liquidity_gross != 0) ditandai 1 bit.
+//   compressed = floor(tick / tick_spacing)
+//   word_pos   = compressed >> 7      (1 word = 128 tick ter-compress)
+//   bit_pos    = compressed & 127
+//
+// Disimpan di persistent storage: DataKey::TickBitmap(word_pos) → u128.
+// Swap loop cuma baca 1 word per step.
+//
+
+const WORD_BITS: i32 = 128;
+
+fn compress(tick: Tick, tick_spacing: i32) -> i32 {
+    tick.div_euclid(tick_spacing)
+}
+
+fn bitmap_position(compressed: i32) -> (i32, u32) {
+    (compressed >> 7, (compressed & (WORD_BITS - 1)) as u32)
+}
+
+fn read_bitmap_word(env: &Env, word_pos: i32) -> u128 {
+    env.storage()
+        .persistent()
+        .get::<_, u128>(&DataKey::TickBitmap(word_pos))
+        .unwrap_or(0)
+}
+
+fn write_bitmap_word(env: &Env, word_pos: i32, word: u128) {
+    let key = DataKey::TickBitmap(word_pos);
+    if word == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &word);
+    }
 }
 
-// --- dummy find_next_initialized_tick buat v0 ---
+// Flip status initialized tick (dipanggil waktu liquidity_gross
+// berubah 0 → != 0 atau != 0 → 0). Tick sudah dicek check_ticks,
+// jadi unaligned di sini = bug → panic.
+pub fn flip_tick(env: &Env, tick: Tick, tick_spacing: i32) {
+    if tick % tick_spacing != 0 {
+        panic_with_error!(env, Error::TickNotAligned);
+    }
 
+    let (word_pos, bit_pos) = bitmap_position(compress(tick, tick_spacing));
+    let word = read_bitmap_word(env, word_pos);
+    write_bitmap_word(env, word_pos, word ^ (1u128 << bit_pos));
+}
+
+// Cari tick initialized berikutnya, maksimal sampai ujung word (1 read).
+//
+//  - zero_for_one (harga turun): cari tick <= current_tick
+//  - one_for_zero (harga naik):  cari tick >  current_tick
+//
+// Return (tick_next, initialized). Kalau di word ini ga ada tick
+// initialized, tick_next = ujung word dan initialized = false
+// (swap loop tinggal lanjut step berikutnya dari situ).
+//
 pub fn find_next_initialized_tick(
-    _env: &Env,
+    env: &Env,
     current_tick: Tick,
     tick_spacing: i32,
     zero_for_one: bool,
-) -> Tick {
+) -> (Tick, bool) {
+    let compressed = compress(current_tick, tick_spacing);
+
     if zero_for_one {
-        current_tick - tick_spacing
+        let (word_pos, bit_pos) = bitmap_position(compressed);
+        // semua bit di posisi bit_pos dan di bawahnya
+        let mask = if bit_pos == 127 {
+            u128::MAX
+        } else {
+            (1u128 << (bit_pos + 1)) - 1
+        };
+        let masked = read_bitmap_word(env, word_pos) & mask;
+
+        if masked != 0 {
+            let msb = 127 - masked.leading_zeros();
+            ((compressed - (bit_pos - msb) as i32) * tick_spacing, true)
+        } else {
+            ((compressed - bit_pos as i32) * tick_spacing, false)
+        }
     } else {
-        current_tick + tick_spacing
+        // mulai dari tick sesudah current
+        let (word_pos, bit_pos) = bitmap_position(compressed + 1);
+        // semua bit di posisi bit_pos dan di atasnya
+        let mask = !((1u128 << bit_pos) - 1);
+        let masked = read_bitmap_word(env, word_pos) & mask;
+
+        if masked != 0 {
+            let lsb = masked.trailing_zeros();
+            ((compressed + 1 + (lsb - bit_pos) as i32) * tick_spacing, true)
+        } else {
+            ((compressed + 1 + (127 - bit_pos) as i32) * tick_spacing, false)
+        }
     }
 }
 
+// ------------------------------------------------------------
+// cross_tick (dipanggil swap loop waktu harga lewat tick)
+// ------------------------------------------------------------
+//
+// Semua *_outside di-flip: outside = global - outside, karena sisi
+// "luar" tick pindah waktu harga lewat. Nilai "global" oracle
+// (tick_cumulative, seconds_per_liquidity) diambil dari observe
+// di waktu swap, seconds global = ledger timestamp.
+//
+// Return liquidity_net tick tsb. Swap loop yang nentuin tanda:
+//  - zero_for_one (harga turun) → liquidity -= net
+//  - one_for_zero (harga naik)  → liquidity += net
+//
+pub fn cross_tick(
+    env: &Env,
+    tick: Tick,
+    fee_growth_global0_x64: u128,
+    fee_growth_global1_x64: u128,
+    tick_cumulative: i64,
+    seconds_per_liquidity_x64: u128,
+    time: u64,
+) -> i128 {
+    let mut info = read_tick_info(env, tick);
+    info.fee_growth_outside0_x64 = fee_growth_global0_x64.wrapping_sub(info.fee_growth_outside0_x64);
+    info.fee_growth_outside1_x64 = fee_growth_global1_x64.wrapping_sub(info.fee_growth_outside1_x64);
+    info.tick_cumulative_outside = tick_cumulative.wrapping_sub(info.tick_cumulative_outside);
+    info.seconds_per_liq_outside_x64 =
+        seconds_per_liquidity_x64.wrapping_sub(info.seconds_per_liq_outside_x64);
+    info.seconds_outside = time.wrapping_sub(info.seconds_outside);
+    write_tick_info(env, tick, &info);
+    info.liquidity_net
+}
+
+// ------------------------------------------------------------
+// get_fee_growth_inside (fee growth di dalam [lower, upper))
+// ------------------------------------------------------------
+//
+//   inside = global - below(lower) - above(upper)
+//
+// Semua pakai wrapping arithmetic: yang penting selisihnya, bukan
+// nilai absolutnya (sama kayak Uniswap V3).
+//
+pub fn get_fee_growth_inside(
+    env: &Env,
+    lower: Tick,
+    upper: Tick,
+    current_tick: Tick,
+    fee_growth_global0_x64: u128,
+    fee_growth_global1_x64: u128,
+) -> (u128, u128) {
+    let lo = read_tick_info(env, lower);
+    let up = read_tick_info(env, upper);
+
+    let (below0, below1) = if current_tick >= lower {
+        (lo.fee_growth_outside0_x64, lo.fee_growth_outside1_x64)
+    } else {
+        (
+            fee_growth_global0_x64.wrapping_sub(lo.fee_growth_outside0_x64),
+            fee_growth_global1_x64.wrapping_sub(lo.fee_growth_outside1_x64),
+        )
+    };
+
+    let (above0, above1) = if current_tick < upper {
+        (up.fee_growth_outside0_x64, up.fee_growth_outside1_x64)
+    } else {
+        (
+            fee_growth_global0_x64.wrapping_sub(up.fee_growth_outside0_x64),
+            fee_growth_global1_x64.wrapping_sub(up.fee_growth_outside1_x64),
+        )
+    };
+
+    (
+        fee_growth_global0_x64.wrapping_sub(below0).wrapping_sub(above0),
+        fee_growth_global1_x64.wrapping_sub(below1).wrapping_sub(above1),
+    )
+}
+
+// ------------------------------------------------------------
+// snapshot_cumulatives_inside (analytics range [lower, upper))
+// ------------------------------------------------------------
+//
+// Return (tick_cumulative_inside, seconds_per_liquidity_inside_x64,
+// seconds_inside). Cuma bermakna sebagai SELISIH antar 2 snapshot
+// selama range tsb tetap initialized — sama kayak Uniswap V3.
+//
+// Dua tick wajib initialized (ada liquidity yang pakai).
+//
+pub fn snapshot_cumulatives_inside(
+    env: &Env,
+    pool: &PoolState,
+    lower: Tick,
+    upper: Tick,
+) -> Result<(i64, u128, u64), Error> {
+    check_ticks(lower, upper, pool.tick_spacing)?;
+
+    let lo = read_tick_info(env, lower);
+    let up = read_tick_info(env, upper);
+    if lo.liquidity_gross == 0 || up.liquidity_gross == 0 {
+        return Err(Error::TickNotInitialized);
+    }
+
+    if pool.current_tick < lower {
+        Ok((
+            lo.tick_cumulative_outside.wrapping_sub(up.tick_cumulative_outside),
+            lo.seconds_per_liq_outside_x64.wrapping_sub(up.seconds_per_liq_outside_x64),
+            lo.seconds_outside.wrapping_sub(up.seconds_outside),
+        ))
+    } else if pool.current_tick < upper {
+        let time = env.ledger().timestamp();
+        let (tick_cumulative, seconds_per_liquidity_x64) = oracle::observe_single(
+            env,
+            time,
+            0,
+            pool.current_tick,
+            pool.observation_index,
+            pool.liquidity,
+            pool.observation_cardinality,
+        )?;
+        Ok((
+            tick_cumulative
+                .wrapping_sub(lo.tick_cumulative_outside)
+                .wrapping_sub(up.tick_cumulative_outside),
+            seconds_per_liquidity_x64
+                .wrapping_sub(lo.seconds_per_liq_outside_x64)
+                .wrapping_sub(up.seconds_per_liq_outside_x64),
+            time.wrapping_sub(lo.seconds_outside).wrapping_sub(up.seconds_outside),
+        ))
+    } else {
+        Ok((
+            up.tick_cumulative_outside.wrapping_sub(lo.tick_cumulative_outside),
+            up.seconds_per_liq_outside_x64.wrapping_sub(lo.seconds_per_liq_outside_x64),
+            up.seconds_outside.wrapping_sub(lo.seconds_outside),
+        ))
+    }
+}
 
 // ------------------------------------------------------------
 // update_tick (untuk add/remove liquidity)
 // ------------------------------------------------------------
 //
-// Digunakan nanti kalau mau wiring langsung add_liquidity/remove_liquidity
-// ke TickInfo via helper ini.
+// Dipanggil add_liquidity (delta +L) dan remove_liquidity (delta -L).
+// Return true kalau tick berubah status initialized (perlu flip_tick).
+//
+// Tick baru di-init: kalau tick <= current_tick, anggap semua fee
+// growth / waktu / cumulative oracle sejauh ini terjadi di bawah tick
+// (outside = global).
+// Tick yang liquidity_gross-nya balik ke 0 dihapus dari storage.
 //
 pub fn update_tick(
     env: &Env,
     tick: Tick,
+    pool: &PoolState,
     delta_liquidity: i128,
     upper: bool,
-) {
+    max_liquidity: i128,
+) -> Result<bool, Error> {
     let mut info = read_tick_info(env, tick);
 
     // Update gross
+    let gross_before = info.liquidity_gross;
     info.liquidity_gross += delta_liquidity;
+    if info.liquidity_gross > max_liquidity {
+        return Err(Error::TickLiquidityOverflow);
+    }
+    let flipped = (gross_before == 0) != (info.liquidity_gross == 0);
+
+    if gross_before == 0 && tick <= pool.current_tick {
+        let time = env.ledger().timestamp();
+        let (tick_cumulative, seconds_per_liquidity_x64) = oracle::observe_single(
+            env,
+            time,
+            0,
+            pool.current_tick,
+            pool.observation_index,
+            pool.liquidity,
+            pool.observation_cardinality,
+        )?;
+
+        info.fee_growth_outside0_x64 = pool.fee_growth_global0_x64;
+        info.fee_growth_outside1_x64 = pool.fee_growth_global1_x64;
+        info.tick_cumulative_outside = tick_cumulative;
+        info.seconds_per_liq_outside_x64 = seconds_per_liquidity_x64;
+        info.seconds_outside = time;
+    }
 
     // Net liquidity:
     //  - tick_lower → upper=false → +L
@@ -68,8 +384,10 @@ pub fn update_tick(
         info.liquidity_net += delta_liquidity;
     }
 
-    write_tick_info(env, tick, &info);
+    if info.liquidity_gross == 0 {
+        clear_tick_info(env, tick);
+    } else {
+        write_tick_info(env, tick, &info);
+    }
+    Ok(flipped)
 }
-
-
-
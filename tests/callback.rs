@@ -0,0 +1,235 @@
+// Test settlement swap_with_callback dan flash: kontrak Payer bayar ke
+// pool di dalam callback, kurang / pas / lebih sebesar `extra` (dikirim
+// lewat data).
+
+mod common;
+
+use clmm_pool::{math, Error, FlashCallback, SwapCallback};
+use common::Setup;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, testutils::Address as _, token,
+    xdr::{FromXdr, ToXdr}, Address, Bytes, Env,
+};
+
+#[contracttype]
+enum PayerKey {
+    Pool,
+    Token0,
+    Token1,
+}
+
+#[contract]
+pub struct Payer;
+
+#[contractimpl]
+impl Payer {
+    pub fn __constructor(env: Env, pool: Address, token0: Address, token1: Address) {
+        let storage = env.storage().instance();
+        storage.set(&PayerKey::Pool, &pool);
+        storage.set(&PayerKey::Token0, &token0);
+        storage.set(&PayerKey::Token1, &token1);
+    }
+}
+
+// transfer dari saldo Payer sendiri ke pool
+fn pay(env: &Env, token_key: PayerKey, amount: i128) {
+    let storage = env.storage().instance();
+    let pool: Address = storage.get(&PayerKey::Pool).unwrap();
+    let token: Address = storage.get(&token_key).unwrap();
+    if amount > 0 {
+        token::Client::new(env, &token).transfer(&env.current_contract_address(), &pool, &amount);
+    }
+}
+
+#[contractimpl]
+impl SwapCallback for Payer {
+    fn swap_callback(env: Env, amount0_delta: i128, amount1_delta: i128, data: Bytes) {
+        let extra = i128::from_xdr(&env, &data).unwrap();
+        if amount0_delta > 0 {
+            pay(&env, PayerKey::Token0, amount0_delta + extra);
+        } else {
+            pay(&env, PayerKey::Token1, amount1_delta + extra);
+        }
+    }
+}
+
+// data = (amount0, amount1, extra): balikin pinjaman + fee + extra
+#[contractimpl]
+impl FlashCallback for Payer {
+    fn flash_callback(env: Env, fee0: i128, fee1: i128, data: Bytes) {
+        let (amount0, amount1, extra) = <(i128, i128, i128)>::from_xdr(&env, &data).unwrap();
+        if amount0 > 0 {
+            pay(&env, PayerKey::Token0, amount0 + fee0 + extra);
+        }
+        if amount1 > 0 {
+            pay(&env, PayerKey::Token1, amount1 + fee1 + extra);
+        }
+    }
+}
+
+const L: i128 = 1_000_000_000_000;
+const AMOUNT: i128 = 1_000_000;
+
+fn setup() -> (Setup<'static>, Address) {
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    s.add_liquidity(&lp, -600, 600, L);
+
+    let payer = s.env.register(
+        Payer,
+        (s.pool.address.clone(), s.token0.address.clone(), s.token1.address.clone()),
+    );
+    for t in [&s.token0.address, &s.token1.address] {
+        token::StellarAssetClient::new(&s.env, t).mint(&payer, &1_000_000_000);
+    }
+    (s, payer)
+}
+
+fn swap_with_callback(
+    s: &Setup,
+    payer: &Address,
+    recipient: &Address,
+    zero_for_one: bool,
+    extra: i128,
+) -> Result<clmm_pool::SwapResult, Error> {
+    let limit_tick = if zero_for_one { -600 } else { 600 };
+    let limit = math::tick_to_sqrt_price_x64(&s.env, limit_tick).unwrap();
+    s.pool
+        .try_swap_with_callback(
+            payer,
+            recipient,
+            &AMOUNT,
+            &zero_for_one,
+            &limit,
+            &extra.to_xdr(&s.env),
+            &u64::MAX,
+        )
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+#[test]
+fn swap_callback_underpaid_reverts() {
+    let (s, payer) = setup();
+    let recipient = Address::generate(&s.env);
+    let before = s.pool.get_pool_state();
+
+    let res = swap_with_callback(&s, &payer, &recipient, true, -1);
+    assert!(matches!(res, Err(Error::InsufficientInputAmount)));
+
+    // token_out yang sudah dikirim ikut di-revert
+    assert_eq!(s.balances(&recipient), (0, 0));
+    assert_eq!(s.pool.get_pool_state().sqrt_price_x64, before.sqrt_price_x64);
+}
+
+#[test]
+fn swap_callback_paid_exactly() {
+    let (s, payer) = setup();
+    let recipient = Address::generate(&s.env);
+    let pool_before = s.balances(&s.pool.address);
+
+    let r = swap_with_callback(&s, &payer, &recipient, false, 0).unwrap();
+    assert_eq!(r.amount_in, AMOUNT);
+
+    assert_eq!(s.balances(&recipient), (r.amount_out, 0));
+    assert_eq!(
+        s.balances(&s.pool.address),
+        (pool_before.0 - r.amount_out, pool_before.1 + r.amount_in)
+    );
+}
+
+#[test]
+fn swap_callback_overpaid_goes_to_fees() {
+    let (s, payer) = setup();
+    let recipient = Address::generate(&s.env);
+
+    // referensi: swap yang sama tanpa kelebihan bayar
+    let (s_ref, payer_ref) = setup();
+    s_ref.pool.set_fee_protocol(&4, &4);
+    swap_with_callback(&s_ref, &payer_ref, &Address::generate(&s_ref.env), true, 0).unwrap();
+    let reference = s_ref.pool.get_pool_state();
+
+    s.pool.set_fee_protocol(&4, &4);
+    let pool_before = s.balances(&s.pool.address);
+    let r = swap_with_callback(&s, &payer, &recipient, true, 1_000).unwrap();
+    let ps = s.pool.get_pool_state();
+
+    assert_eq!(s.balances(&s.pool.address).0, pool_before.0 + r.amount_in + 1_000);
+
+    // 1/4 dari kelebihan ke protocol, 3/4 ke LP in-range
+    assert_eq!(ps.protocol_fees0, reference.protocol_fees0 + 250);
+    assert_eq!(
+        ps.fee_growth_global0_x64,
+        reference.fee_growth_global0_x64 + math::fee_growth_delta_x64(&s.env, 750, L).unwrap()
+    );
+    assert_eq!(ps.fee_growth_global1_x64, 0);
+}
+
+// -------------------------------------------------------------
+// flash
+// -------------------------------------------------------------
+
+// fee = ceil(1_000_000 * 30 / 10_000)
+const FLASH_FEE: i128 = 3_000;
+
+fn flash(s: &Setup, payer: &Address, amount0: i128, amount1: i128, extra: i128) -> Result<(i128, i128), Error> {
+    s.pool
+        .try_flash(payer, &amount0, &amount1, &(amount0, amount1, extra).to_xdr(&s.env))
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+#[test]
+fn flash_underpaid_reverts() {
+    let (s, payer) = setup();
+    let payer_before = s.balances(&payer);
+    let before = s.pool.get_pool_state();
+
+    let res = flash(&s, &payer, AMOUNT, 0, -1);
+    assert!(matches!(res, Err(Error::InsufficientInputAmount)));
+
+    assert_eq!(s.balances(&payer), payer_before);
+    assert_eq!(s.pool.get_pool_state().fee_growth_global0_x64, before.fee_growth_global0_x64);
+}
+
+#[test]
+fn flash_paid_exactly() {
+    let (s, payer) = setup();
+    let pool_before = s.balances(&s.pool.address);
+
+    assert_eq!(flash(&s, &payer, AMOUNT, AMOUNT, 0), Ok((FLASH_FEE, FLASH_FEE)));
+
+    assert_eq!(
+        s.balances(&s.pool.address),
+        (pool_before.0 + FLASH_FEE, pool_before.1 + FLASH_FEE)
+    );
+    let ps = s.pool.get_pool_state();
+    let growth = math::fee_growth_delta_x64(&s.env, FLASH_FEE, L).unwrap();
+    assert_eq!((ps.fee_growth_global0_x64, ps.fee_growth_global1_x64), (growth, growth));
+    assert_eq!((ps.protocol_fees0, ps.protocol_fees1), (0, 0));
+}
+
+#[test]
+fn flash_overpaid_goes_to_fees() {
+    let (s, payer) = setup();
+    s.pool.set_fee_protocol(&4, &0);
+
+    // bayar fee 3_000 + 1_000 → protocol 1/4, sisanya LP
+    assert_eq!(flash(&s, &payer, AMOUNT, 0, 1_000), Ok((FLASH_FEE + 1_000, 0)));
+
+    let ps = s.pool.get_pool_state();
+    assert_eq!(ps.protocol_fees0, 1_000);
+    assert_eq!(ps.fee_growth_global0_x64, math::fee_growth_delta_x64(&s.env, 3_000, L).unwrap());
+    assert_eq!(ps.fee_growth_global1_x64, 0);
+}
+
+#[test]
+fn flash_without_active_liquidity_fails() {
+    let (s, payer) = setup();
+    // geser harga keluar range [-600, 600) → liquidity aktif 0
+    let trader = s.user(1_000_000_000_000_000);
+    s.swap_to_tick(&trader, 1_000_000_000_000_000, false, 1200);
+    assert_eq!(s.pool.get_pool_state().liquidity, 0);
+
+    assert_eq!(flash(&s, &payer, AMOUNT, 0, 0), Err(Error::NoActiveLiquidity));
+}
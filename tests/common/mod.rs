@@ -0,0 +1,111 @@
+// Helper bersama test kontrak: pool + 2 token SAC, sudah di-initialize
+// di harga 1.0 (tick 0), atau di tick lain lewat Setup::at_tick.
+#![allow(dead_code)]
+
+use clmm_pool::{math, ClmmPool, ClmmPoolClient};
+use soroban_sdk::{testutils::Address as _, token, Address, Env};
+
+pub const FEE_BPS: u32 = 30;
+pub const TICK_SPACING: i32 = 60;
+
+fn default_env() -> Env {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+    env
+}
+
+pub struct Setup<'a> {
+    pub env: Env,
+    pub admin: Address,
+    pub pool: ClmmPoolClient<'a>,
+    pub token0: token::Client<'a>,
+    pub token1: token::Client<'a>,
+}
+
+impl Setup<'_> {
+    pub fn new() -> Self {
+        Self::with_env(default_env())
+    }
+
+    // pool di-initialize di harga tick `tick` (bukan 1.0)
+    pub fn at_tick(tick: i32) -> Self {
+        let env = default_env();
+        let sqrt_price = math::tick_to_sqrt_price_x64(&env, tick).unwrap();
+        Self::init(env, sqrt_price, tick)
+    }
+
+    // env sudah disiapin caller (auth, ledger, budget)
+    pub fn with_env(env: Env) -> Self {
+        Self::init(env, 0, 0)
+    }
+
+    fn init(env: Env, sqrt_price_x64: u128, current_tick: i32) -> Self {
+        let admin = Address::generate(&env);
+
+        let a = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        let b = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        let (token0, token1) = if a < b { (a, b) } else { (b, a) };
+
+        let pool_id = env.register(ClmmPool, ());
+        let pool = ClmmPoolClient::new(&env, &pool_id);
+        pool.mock_all_auths()
+            .initialize(
+                &admin,
+                &token0,
+                &token1,
+                &FEE_BPS,
+                &sqrt_price_x64,
+                &current_tick,
+                &TICK_SPACING,
+            );
+
+        Setup {
+            token0: token::Client::new(&env, &token0),
+            token1: token::Client::new(&env, &token1),
+            env,
+            admin,
+            pool,
+        }
+    }
+
+    // address baru dengan saldo token0 + token1
+    pub fn user(&self, amount: i128) -> Address {
+        let user = Address::generate(&self.env);
+        for t in [&self.token0.address, &self.token1.address] {
+            token::StellarAssetClient::new(&self.env, t)
+                .mock_all_auths()
+                .mint(&user, &amount);
+        }
+        user
+    }
+
+    pub fn add_liquidity(&self, owner: &Address, lower: i32, upper: i32, liquidity: i128) -> (i128, i128) {
+        self.pool.add_liquidity(
+            owner,
+            &lower,
+            &upper,
+            &liquidity,
+            &i128::MAX,
+            &i128::MAX,
+            &u64::MAX,
+        )
+    }
+
+    // exact input, berhenti paling jauh di harga tick `limit_tick`
+    pub fn swap_to_tick(&self, caller: &Address, amount: i128, zero_for_one: bool, limit_tick: i32) -> clmm_pool::SwapResult {
+        self.pool.swap(
+            caller,
+            &amount,
+            &zero_for_one,
+            &math::tick_to_sqrt_price_x64(&self.env, limit_tick).unwrap(),
+            &0,
+            &i128::MAX,
+            &u64::MAX,
+        )
+    }
+
+    pub fn balances(&self, who: &Address) -> (i128, i128) {
+        (self.token0.balance(who), self.token1.balance(who))
+    }
+}
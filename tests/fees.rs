@@ -0,0 +1,139 @@
+// Test fee LP: fee growth global, nilai outside per tick waktu cross,
+// dan settle ke tokens_owed → collect.
+
+mod common;
+
+use clmm_pool::{math, Error};
+use common::{Setup, FEE_BPS};
+use soroban_sdk::{testutils::Address as _, Address};
+
+const L_A: i128 = 1_000_000_000_000; // [-600, 600)
+const L_B: i128 = 3_000_000_000_000; // [-1200, 1200)
+const L_C: i128 = 2_000_000_000_000; // [600, 1200), di luar range semua swap
+
+const BIG: i128 = 1_000_000_000_000_000;
+
+// collect semua tokens_owed posisi ke owner-nya
+fn collect_all(s: &Setup, owner: &Address, lower: i32, upper: i32) -> (i128, i128) {
+    s.pool.collect(owner, owner, &lower, &upper, owner, &i128::MAX, &i128::MAX)
+}
+
+#[test]
+fn fees_split_by_in_range_liquidity_across_crossings() {
+    let s = Setup::new();
+    let a = s.user(BIG);
+    let b = s.user(BIG);
+    let c = s.user(BIG);
+    let trader = s.user(BIG);
+
+    s.add_liquidity(&a, -600, 600, L_A);
+    s.add_liquidity(&b, -1200, 1200, L_B);
+    s.add_liquidity(&c, 600, 1200, L_C);
+
+    // 1) 0 → -600 (cross -600 di ujung): A dan B in-range, fee token0 1:3
+    s.swap_to_tick(&trader, BIG, true, -600);
+    let (a0_first, a1) = collect_all(&s, &a, -600, 600);
+    let (b0, b1) = collect_all(&s, &b, -1200, 1200);
+    assert!(a0_first > 0);
+    assert_eq!((a1, b1), (0, 0));
+    assert!((b0 - 3 * a0_first).abs() <= 3, "a {} b {}", a0_first, b0);
+
+    // 2) -600 → 0 (cross -600 lagi, A masuk range): fee token1 1:3
+    s.swap_to_tick(&trader, BIG, false, 0);
+    let (a0, a1) = collect_all(&s, &a, -600, 600);
+    let (b0, b1) = collect_all(&s, &b, -1200, 1200);
+    assert_eq!((a0, b0), (0, 0));
+    assert!(a1 > 0);
+    assert!((b1 - 3 * a1).abs() <= 3, "a {} b {}", a1, b1);
+
+    // 3) 0 → -900: A cuma dapet bagian 0 → -600 (sama kayak swap 1),
+    //    -600 → -900 fee-nya full ke B
+    s.swap_to_tick(&trader, BIG, true, -900);
+    let (a0, _) = collect_all(&s, &a, -600, 600);
+    let (b0, _) = collect_all(&s, &b, -1200, 1200);
+    assert!((a0 - a0_first).abs() <= 1);
+    assert!(b0 > 3 * a0 + 1_000);
+
+    // C ga pernah in-range → ga dapet fee
+    assert_eq!(collect_all(&s, &c, 600, 1200), (0, 0));
+    let pc = s.pool.get_position(&c, &600, &1200);
+    assert_eq!((pc.tokens_owed0, pc.tokens_owed1), (0, 0));
+
+    // burn semua + collect → pool tinggal debu pembulatan
+    for (owner, lower, upper, l) in [(&a, -600, 600, L_A), (&b, -1200, 1200, L_B), (&c, 600, 1200, L_C)] {
+        s.pool.remove_liquidity(owner, owner, &lower, &upper, &l, &0, &0, &u64::MAX);
+        collect_all(&s, owner, lower, upper);
+        let pos = s.pool.get_position(owner, &lower, &upper);
+        assert_eq!((pos.liquidity, pos.tokens_owed0, pos.tokens_owed1), (0, 0, 0));
+    }
+
+    let ps = s.pool.get_pool_state();
+    assert_eq!(ps.liquidity, 0);
+    let (left0, left1) = s.balances(&s.pool.address);
+    assert!((0..=10).contains(&left0) && (0..=10).contains(&left1), "{} {}", left0, left1);
+}
+
+#[test]
+fn set_fee_protocol_rejects_invalid_values() {
+    let s = Setup::new();
+
+    for n in [1, 2, 3, 11, u32::MAX] {
+        assert_eq!(s.pool.try_set_fee_protocol(&n, &0), Err(Ok(Error::InvalidFeeProtocol)));
+        assert_eq!(s.pool.try_set_fee_protocol(&0, &n), Err(Ok(Error::InvalidFeeProtocol)));
+    }
+    let ps = s.pool.get_pool_state();
+    assert_eq!((ps.fee_protocol0, ps.fee_protocol1), (0, 0));
+
+    // 0 (off) dan 4..=10 valid
+    for n in [0, 4, 10] {
+        s.pool.set_fee_protocol(&n, &n);
+    }
+    let ps = s.pool.get_pool_state();
+    assert_eq!((ps.fee_protocol0, ps.fee_protocol1), (10, 10));
+}
+
+// Fee switch nyala: 1/N fee swap masuk protocol_fees, sisanya ke
+// fee_growth LP. collect_protocol bayar semuanya lalu reset ke 0.
+#[test]
+fn protocol_fee_split_and_collect() {
+    const AMOUNT: i128 = 1_000_000_000;
+
+    let s = Setup::new();
+    let lp = s.user(BIG);
+    let trader = s.user(BIG);
+    s.add_liquidity(&lp, -600, 600, L_A);
+    s.pool.set_fee_protocol(&4, &5);
+
+    // fee 1 step ke arah limit_tick (ga nyampe limit → fee = sisa input di luar kurva)
+    let step_fee = |limit_tick: i32| {
+        let ps = s.pool.get_pool_state();
+        let limit = math::tick_to_sqrt_price_x64(&s.env, limit_tick).unwrap();
+        math::compute_swap_step(&s.env, ps.sqrt_price_x64, limit, L_A, AMOUNT, FEE_BPS)
+            .unwrap()
+            .3
+    };
+
+    let fee0 = step_fee(-600);
+    s.swap_to_tick(&trader, AMOUNT, true, -600);
+    let fee1 = step_fee(600);
+    s.swap_to_tick(&trader, AMOUNT, false, 600);
+
+    let ps = s.pool.get_pool_state();
+    assert_eq!((ps.protocol_fees0, ps.protocol_fees1), (fee0 / 4, fee1 / 5));
+    let (share0, share1) = (fee0 - fee0 / 4, fee1 - fee1 / 5);
+    assert_eq!(ps.fee_growth_global0_x64, math::fee_growth_delta_x64(&s.env, share0, L_A).unwrap());
+    assert_eq!(ps.fee_growth_global1_x64, math::fee_growth_delta_x64(&s.env, share1, L_A).unwrap());
+
+    // LP cuma dapet bagian LP (pembulatan max 1)
+    let (lp0, lp1) = collect_all(&s, &lp, -600, 600);
+    assert!(lp0 <= share0 && share0 - lp0 <= 1, "{} {}", lp0, share0);
+    assert!(lp1 <= share1 && share1 - lp1 <= 1, "{} {}", lp1, share1);
+
+    let treasury = Address::generate(&s.env);
+    assert_eq!(s.pool.collect_protocol(&treasury), (fee0 / 4, fee1 / 5));
+    assert_eq!(s.balances(&treasury), (fee0 / 4, fee1 / 5));
+
+    let ps = s.pool.get_pool_state();
+    assert_eq!((ps.protocol_fees0, ps.protocol_fees1), (0, 0));
+    assert_eq!(s.pool.collect_protocol(&treasury), (0, 0));
+}
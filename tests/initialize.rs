@@ -0,0 +1,109 @@
+// Test validasi initialize: urutan token, initialize ulang, dan
+// konsistensi sqrt_price_x64 ↔ current_tick.
+
+mod common;
+
+use clmm_pool::{math, ClmmPool, ClmmPoolClient, Error};
+use common::{Setup, FEE_BPS, TICK_SPACING};
+use soroban_sdk::{testutils::Address as _, Address, Env};
+
+struct Uninit<'a> {
+    env: Env,
+    admin: Address,
+    pool: ClmmPoolClient<'a>,
+    token0: Address,
+    token1: Address,
+}
+
+// pool baru yang belum di-initialize + 2 token urut by address
+fn uninit() -> Uninit<'static> {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+
+    let a = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let b = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let (token0, token1) = if a < b { (a, b) } else { (b, a) };
+
+    let pool = ClmmPoolClient::new(&env, &env.register(ClmmPool, ()));
+    Uninit { env, admin, pool, token0, token1 }
+}
+
+impl Uninit<'_> {
+    fn try_init(
+        &self,
+        token_a: &Address,
+        token_b: &Address,
+        sqrt_price: u128,
+        tick: i32,
+    ) -> Result<(), Error> {
+        let admin = &self.admin;
+        self.pool
+            .try_initialize(admin, token_a, token_b, &FEE_BPS, &sqrt_price, &tick, &TICK_SPACING)
+            .map(|r| r.unwrap())
+            .map_err(|e| e.unwrap())
+    }
+}
+
+#[test]
+fn initialize_rejects_unsorted_tokens() {
+    let u = uninit();
+
+    assert_eq!(u.try_init(&u.token1, &u.token0, 0, 0), Err(Error::TokensNotSorted));
+    assert_eq!(u.try_init(&u.token0, &u.token0, 0, 0), Err(Error::IdenticalTokens));
+    assert_eq!(u.pool.try_get_pool_state().err(), Some(Ok(Error::NotInitialized)));
+
+    assert_eq!(u.try_init(&u.token0, &u.token1, 0, 0), Ok(()));
+    let cfg = u.pool.get_pool_config();
+    assert_eq!((cfg.token_a, cfg.token_b), (u.token0, u.token1));
+}
+
+#[test]
+fn initialize_twice_is_rejected() {
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    s.add_liquidity(&lp, -600, 600, 1_000_000_000);
+    let before = s.pool.get_pool_state();
+
+    // initialize ulang (harga lain) ga boleh nimpa state yang ada
+    let sqrt_price = math::tick_to_sqrt_price_x64(&s.env, 6_000).unwrap();
+    let r = s.pool.try_initialize(
+        &s.admin,
+        &s.token0.address,
+        &s.token1.address,
+        &FEE_BPS,
+        &sqrt_price,
+        &6_000,
+        &TICK_SPACING,
+    );
+    assert_eq!(r, Err(Ok(Error::AlreadyInitialized)));
+
+    let after = s.pool.get_pool_state();
+    assert_eq!(
+        (after.sqrt_price_x64, after.current_tick, after.liquidity),
+        (before.sqrt_price_x64, before.current_tick, before.liquidity)
+    );
+}
+
+#[test]
+fn initialize_rejects_inconsistent_price() {
+    let u = uninit();
+    let init = |sqrt_price: u128, tick: i32| u.try_init(&u.token0, &u.token1, sqrt_price, tick);
+
+    let sqrt_600 = math::tick_to_sqrt_price_x64(&u.env, 600).unwrap();
+
+    // current_tick harus = sqrt_price_to_tick(sqrt_price)
+    assert_eq!(init(sqrt_600, 0), Err(Error::InvalidPrice));
+    assert_eq!(init(sqrt_600, 601), Err(Error::InvalidPrice));
+    assert_eq!(init(sqrt_600 - 1, 600), Err(Error::InvalidPrice));
+    // default harga 1.0 (sqrt 0) → tick 0
+    assert_eq!(init(0, 600), Err(Error::InvalidPrice));
+
+    // di luar MIN / MAX_SQRT_PRICE_X64
+    assert_eq!(init(math::MIN_SQRT_PRICE_X64 - 1, -443_636), Err(Error::InvalidPrice));
+    assert_eq!(init(math::MAX_SQRT_PRICE_X64 + 1, 443_636), Err(Error::InvalidPrice));
+
+    assert_eq!(init(sqrt_600 - 1, 599), Ok(()));
+    let ps = u.pool.get_pool_state();
+    assert_eq!((ps.sqrt_price_x64, ps.current_tick), (sqrt_600 - 1, 599));
+}
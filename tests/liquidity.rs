@@ -0,0 +1,83 @@
+// Test add_liquidity lewat entrypoint kontrak: validasi range tick dan
+// batas liquidity / amount. Semua gagal dengan error kontrak, bukan panic.
+
+mod common;
+
+use clmm_pool::Error;
+use common::{Setup, TICK_SPACING};
+
+// range tick terlebar yang align ke TICK_SPACING
+const FULL_LOWER: i32 = -443_580;
+const FULL_UPPER: i32 = 443_580;
+
+// sama dengan tick::max_liquidity_per_tick(TICK_SPACING)
+fn max_liquidity_per_tick() -> i128 {
+    let num_ticks = ((FULL_UPPER - FULL_LOWER) / TICK_SPACING) as i128 + 1;
+    i128::MAX / num_ticks
+}
+
+fn try_add(s: &Setup, lower: i32, upper: i32, liquidity: i128) -> Result<(i128, i128), Error> {
+    let owner = s.user(i128::MAX);
+    s.pool
+        .try_add_liquidity(&owner, &lower, &upper, &liquidity, &i128::MAX, &i128::MAX, &u64::MAX)
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+// Liquidity pas di batas per tick: di harga 1.0 amount-nya masih muat,
+// tapi di harga tinggi amount1 = L * (sqrtP - sqrtL) lewat i128 (u128).
+#[test]
+fn add_liquidity_near_max_per_tick_returns_overflow_error() {
+    let max = max_liquidity_per_tick();
+
+    let s = Setup::new();
+    let (amount0, amount1) = try_add(&s, FULL_LOWER, FULL_UPPER, max).unwrap();
+    assert!(amount0 > 0 && amount1 > 0);
+
+    // amount1 ≈ 2.3e38: muat di u128, ga muat di i128
+    let s = Setup::at_tick(198_000);
+    assert_eq!(try_add(&s, FULL_LOWER, FULL_UPPER, max), Err(Error::AmountOverflow));
+
+    // amount1 ≈ 3.8e40: udah ga muat di u128
+    let s = Setup::at_tick(300_000);
+    assert_eq!(try_add(&s, FULL_LOWER, FULL_UPPER, max), Err(Error::MathOverflow));
+
+    // gagal → ga ada yang ketulis
+    assert_eq!(s.pool.get_pool_state().liquidity, 0);
+    assert_eq!(s.pool.get_tick_info(&FULL_LOWER).liquidity_gross, 0);
+}
+
+#[test]
+fn add_liquidity_rejects_invalid_ticks() {
+    let s = Setup::new();
+
+    // bukan kelipatan tick_spacing (60)
+    assert_eq!(try_add(&s, -10, 10, 1_000), Err(Error::TickNotAligned));
+    assert_eq!(try_add(&s, -600, 630, 1_000), Err(Error::TickNotAligned));
+
+    // kelipatan 60 tapi di luar MIN_TICK / MAX_TICK
+    assert_eq!(try_add(&s, FULL_LOWER - 60, 600, 1_000), Err(Error::TickOutOfRange));
+    assert_eq!(try_add(&s, -600, FULL_UPPER + 60, 1_000), Err(Error::TickOutOfRange));
+
+    // range kebalik / kosong
+    assert_eq!(try_add(&s, 600, -600, 1_000), Err(Error::InvalidTickRange));
+    assert_eq!(try_add(&s, 600, 600, 1_000), Err(Error::InvalidTickRange));
+
+    assert!(try_add(&s, -600, 600, 1_000).is_ok());
+}
+
+#[test]
+fn add_liquidity_rejects_tick_liquidity_overflow() {
+    let max = max_liquidity_per_tick();
+    let s = Setup::new();
+
+    assert_eq!(try_add(&s, -600, 600, max + 1), Err(Error::TickLiquidityOverflow));
+
+    // pas di batas masih boleh, tapi tick -600 / 600 udah penuh
+    try_add(&s, -600, 600, max).unwrap();
+    assert_eq!(try_add(&s, -600, 1200, 1), Err(Error::TickLiquidityOverflow));
+    assert_eq!(s.pool.get_tick_info(&-600).liquidity_gross, max);
+
+    // tick lain masih bebas
+    assert!(try_add(&s, 1200, 1800, 1).is_ok());
+}
@@ -0,0 +1,340 @@
+// Test math CLMM (Q64.64).
+//
+// Vector compute_swap_step diambil dari SwapMath.spec.ts Uniswap V3,
+// harga dikonversi dari Q64.96 ke Q64.64 dan fee dari pips ke bps
+// (600 pips = 6 bps, 3000 pips = 30 bps).
+
+use clmm_pool::math::{
+    compute_swap_step, get_amounts_for_liquidity, get_liquidity_for_amount0,
+    get_liquidity_for_amount1, sqrt_price_to_tick, tick_to_sqrt_price_x64, MAX_SQRT_PRICE_X64,
+    MIN_SQRT_PRICE_X64,
+};
+use clmm_pool::Error;
+use soroban_sdk::Env;
+
+const MIN_TICK: i32 = -443_636;
+const MAX_TICK: i32 = 443_636;
+
+const ONE_X64: u128 = 1u128 << 64;
+
+// floor(sqrt(101/100) * 2^64)
+const SQRT_101_100_X64: u128 = 18_538_748_355_542_988_169;
+// floor(sqrt(100/101) * 2^64)
+const SQRT_100_101_X64: u128 = 18_355_196_391_626_720_960;
+// floor(sqrt(1000/100) * 2^64)
+const SQRT_1000_100_X64: u128 = 58_333_726_687_135_158_848;
+// sqrt(10000/100) * 2^64
+const SQRT_10000_100_X64: u128 = 184_467_440_737_095_516_160;
+
+const E18: i128 = 1_000_000_000_000_000_000;
+
+#[test]
+fn exact_in_capped_at_price_target_one_for_zero() {
+    let env = Env::default();
+
+    let (sqrt_q, amount_in, amount_out, fee) =
+        compute_swap_step(&env, ONE_X64, SQRT_101_100_X64, 2 * E18, E18, 6).unwrap();
+
+    // Uniswap (Q64.96): 9975124224178055 — beda 1 karena presisi target Q64.64
+    assert_eq!(amount_in, 9_975_124_224_178_054);
+    assert_eq!(fee, 5_988_667_735_148);
+    assert_eq!(amount_out, 9_925_619_580_021_728);
+    assert!(amount_in + fee < E18);
+    assert_eq!(sqrt_q, SQRT_101_100_X64);
+}
+
+#[test]
+fn exact_out_capped_at_price_target_one_for_zero() {
+    let env = Env::default();
+
+    let (sqrt_q, amount_in, amount_out, fee) =
+        compute_swap_step(&env, ONE_X64, SQRT_101_100_X64, 2 * E18, -E18, 6).unwrap();
+
+    assert_eq!(amount_in, 9_975_124_224_178_054);
+    assert_eq!(fee, 5_988_667_735_148);
+    assert_eq!(amount_out, 9_925_619_580_021_728);
+    assert!(amount_out < E18);
+    assert_eq!(sqrt_q, SQRT_101_100_X64);
+}
+
+#[test]
+fn exact_in_fully_spent_one_for_zero() {
+    let env = Env::default();
+
+    let (sqrt_q, amount_in, amount_out, fee) =
+        compute_swap_step(&env, ONE_X64, SQRT_1000_100_X64, 2 * E18, E18, 6).unwrap();
+
+    assert_eq!(amount_in, 999_400_000_000_000_000);
+    assert_eq!(fee, 600_000_000_000_000);
+    assert_eq!(amount_out, 666_399_946_655_997_866);
+    assert_eq!(amount_in + fee, E18);
+    assert!(sqrt_q < SQRT_1000_100_X64);
+}
+
+#[test]
+fn exact_out_fully_received_one_for_zero() {
+    let env = Env::default();
+
+    let (sqrt_q, amount_in, amount_out, fee) =
+        compute_swap_step(&env, ONE_X64, SQRT_10000_100_X64, 2 * E18, -E18, 6).unwrap();
+
+    assert_eq!(amount_in, 2 * E18);
+    assert_eq!(fee, 1_200_720_432_259_356);
+    assert_eq!(amount_out, E18);
+    assert!(sqrt_q < SQRT_10000_100_X64);
+}
+
+#[test]
+fn exact_in_and_out_capped_at_price_target_zero_for_one() {
+    let env = Env::default();
+
+    let exact_in = compute_swap_step(&env, ONE_X64, SQRT_100_101_X64, 2 * E18, E18, 6).unwrap();
+    let exact_out = compute_swap_step(&env, ONE_X64, SQRT_100_101_X64, 2 * E18, -E18, 6).unwrap();
+
+    // sama persis dengan vector Uniswap arah sebaliknya
+    let expected = (
+        SQRT_100_101_X64,
+        9_975_124_224_178_055,
+        9_925_619_580_021_728,
+        5_988_667_735_148,
+    );
+    assert_eq!(exact_in, expected);
+    assert_eq!(exact_out, expected);
+}
+
+#[test]
+fn entire_input_amount_taken_as_fee() {
+    let env = Env::default();
+
+    // fee Uniswap 1872 pips → dibulatkan 19 bps
+    let (sqrt_q, amount_in, amount_out, fee) = compute_swap_step(
+        &env,
+        2413,
+        79_887_613_182_836_312,
+        1_985_041_575_832_132_834_610_021_537_970,
+        10,
+        19,
+    ).unwrap();
+
+    assert_eq!(amount_in, 0);
+    assert_eq!(fee, 10);
+    assert_eq!(amount_out, 0);
+    assert_eq!(sqrt_q, 2413);
+}
+
+#[test]
+fn intermediate_insufficient_liquidity_one_for_zero_exact_output() {
+    let env = Env::default();
+
+    let sqrt_p = 256 * ONE_X64;
+    let sqrt_p_target = sqrt_p * 11 / 10;
+
+    let (sqrt_q, amount_in, amount_out, fee) =
+        compute_swap_step(&env, sqrt_p, sqrt_p_target, 1024, -4, 30).unwrap();
+
+    assert_eq!(amount_out, 0);
+    assert_eq!(sqrt_q, sqrt_p_target);
+    assert_eq!(amount_in, 26_215);
+    assert_eq!(fee, 79);
+}
+
+#[test]
+fn intermediate_insufficient_liquidity_zero_for_one_exact_output() {
+    let env = Env::default();
+
+    let sqrt_p = 256 * ONE_X64;
+    let sqrt_p_target = sqrt_p * 9 / 10;
+
+    let (sqrt_q, amount_in, amount_out, fee) =
+        compute_swap_step(&env, sqrt_p, sqrt_p_target, 1024, -263_000, 30).unwrap();
+
+    assert_eq!(amount_out, 26_214);
+    assert_eq!(sqrt_q, sqrt_p_target);
+    assert_eq!(amount_in, 1);
+    assert_eq!(fee, 1);
+}
+
+// -------------------------------------------------------------
+// tick <-> sqrt_price
+// -------------------------------------------------------------
+
+#[test]
+fn tick_to_sqrt_price_bounds() {
+    let env = Env::default();
+
+    assert_eq!(tick_to_sqrt_price_x64(&env, 0).unwrap(), ONE_X64);
+    assert_eq!(tick_to_sqrt_price_x64(&env, MIN_TICK).unwrap(), MIN_SQRT_PRICE_X64);
+    assert_eq!(tick_to_sqrt_price_x64(&env, MAX_TICK).unwrap(), MAX_SQRT_PRICE_X64);
+    assert_eq!(sqrt_price_to_tick(&env, MIN_SQRT_PRICE_X64).unwrap(), MIN_TICK);
+    assert_eq!(sqrt_price_to_tick(&env, MAX_SQRT_PRICE_X64).unwrap(), MAX_TICK);
+}
+
+#[test]
+fn tick_to_sqrt_price_above_max_tick() {
+    let env = Env::default();
+    assert_eq!(tick_to_sqrt_price_x64(&env, MAX_TICK + 1), Err(Error::TickOutOfRange));
+}
+
+#[test]
+fn sqrt_price_to_tick_below_min_sqrt_price() {
+    let env = Env::default();
+    assert_eq!(sqrt_price_to_tick(&env, MIN_SQRT_PRICE_X64 - 1), Err(Error::InvalidPrice));
+}
+
+// Property: untuk tick t,
+//   sqrt_price_to_tick(sqrt(t))     == t
+//   sqrt_price_to_tick(sqrt(t) - 1) == t - 1
+//   sqrt_price_to_tick(sqrt(t+1)-1) == t
+// Tick diambil dari LCG deterministik di seluruh range + sekitar 0.
+#[test]
+fn sqrt_price_to_tick_round_trips_tick_to_sqrt_price() {
+    let env = Env::default();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let mut ticks = std::vec::Vec::new();
+    for t in -50..=50 {
+        ticks.push(t);
+    }
+    ticks.extend_from_slice(&[MIN_TICK + 1, MAX_TICK - 1, -200_000, 200_000]);
+
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let span = (MAX_TICK - MIN_TICK - 1) as u64;
+    for _ in 0..500 {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ticks.push(MIN_TICK + 1 + ((seed >> 33) % span) as i32);
+    }
+
+    for t in ticks {
+        let sqrt_p = tick_to_sqrt_price_x64(&env, t).unwrap();
+        let sqrt_p_next = tick_to_sqrt_price_x64(&env, t + 1).unwrap();
+
+        assert!(sqrt_p < sqrt_p_next, "not monotonic at tick {}", t);
+        assert_eq!(sqrt_price_to_tick(&env, sqrt_p).unwrap(), t);
+        assert_eq!(sqrt_price_to_tick(&env, sqrt_p - 1).unwrap(), t - 1);
+        assert_eq!(sqrt_price_to_tick(&env, sqrt_p_next - 1).unwrap(), t);
+    }
+}
+
+// -------------------------------------------------------------
+// liquidity <-> amount (posisi LP)
+// -------------------------------------------------------------
+
+const LIQ: i128 = 1_000_000_007;
+
+#[test]
+fn amounts_for_liquidity_below_range_is_all_token0() {
+    let env = Env::default();
+    let lower = tick_to_sqrt_price_x64(&env, -600).unwrap();
+    let upper = tick_to_sqrt_price_x64(&env, 600).unwrap();
+    let below = tick_to_sqrt_price_x64(&env, -1200).unwrap();
+
+    let (a0, a1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, below, false).unwrap();
+    assert!(a0 > 0);
+    assert_eq!(a1, 0);
+
+    // harga persis di lower = masih di bawah range
+    assert_eq!(get_amounts_for_liquidity(&env, LIQ, lower, upper, lower, false).unwrap(), (a0, 0));
+    // urutan lower / upper ga ngaruh
+    assert_eq!(get_amounts_for_liquidity(&env, LIQ, upper, lower, below, false).unwrap(), (a0, 0));
+}
+
+#[test]
+fn amounts_for_liquidity_above_range_is_all_token1() {
+    let env = Env::default();
+    let lower = tick_to_sqrt_price_x64(&env, -600).unwrap();
+    let upper = tick_to_sqrt_price_x64(&env, 600).unwrap();
+    let above = tick_to_sqrt_price_x64(&env, 1200).unwrap();
+
+    let (a0, a1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, above, false).unwrap();
+    assert_eq!(a0, 0);
+    // amount1 = floor(L * (sqrtU - sqrtL) / 2^64)
+    assert_eq!(a1, (LIQ as u128 * (upper - lower) / ONE_X64) as i128);
+
+    // harga persis di upper = sudah di atas range
+    assert_eq!(get_amounts_for_liquidity(&env, LIQ, lower, upper, upper, false).unwrap(), (0, a1));
+}
+
+#[test]
+fn amounts_for_liquidity_in_range_is_both_tokens() {
+    let env = Env::default();
+    let lower = tick_to_sqrt_price_x64(&env, -600).unwrap();
+    let upper = tick_to_sqrt_price_x64(&env, 600).unwrap();
+
+    let (below0, _) = get_amounts_for_liquidity(&env, LIQ, lower, upper, lower, false).unwrap();
+    let (_, above1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, upper, false).unwrap();
+    let (a0, a1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, ONE_X64, false).unwrap();
+
+    assert!(a0 > 0 && a0 < below0);
+    assert!(a1 > 0 && a1 < above1);
+    assert_eq!(a1, (LIQ as u128 * (ONE_X64 - lower) / ONE_X64) as i128);
+}
+
+#[test]
+fn amounts_for_liquidity_round_up_vs_down() {
+    let env = Env::default();
+    let lower = tick_to_sqrt_price_x64(&env, -600).unwrap();
+    let upper = tick_to_sqrt_price_x64(&env, 600).unwrap();
+
+    for current in [tick_to_sqrt_price_x64(&env, -1200).unwrap(), lower, ONE_X64, upper] {
+        let (down0, down1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, current, false).unwrap();
+        let (up0, up1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, current, true).unwrap();
+
+        // add (round up) ga pernah lebih murah dari remove (round down), beda max 1
+        assert!(up0 - down0 == 0 || up0 - down0 == 1);
+        assert!(up1 - down1 == 0 || up1 - down1 == 1);
+    }
+
+    // hasil bagi ga bulat → round up persis +1
+    let (down0, down1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, ONE_X64, false).unwrap();
+    let (up0, up1) = get_amounts_for_liquidity(&env, LIQ, lower, upper, ONE_X64, true).unwrap();
+    assert_eq!(up0, down0 + 1);
+    assert_eq!(up1, down1 + 1);
+
+    // liquidity 0 → 0 di dua arah
+    assert_eq!(get_amounts_for_liquidity(&env, 0, lower, upper, ONE_X64, true).unwrap(), (0, 0));
+}
+
+// Property: liquidity dari amount (round down) kalau dihitung balik
+// (round down) ga pernah lebih dari amount input.
+#[test]
+fn liquidity_for_amount_round_trip_never_exceeds_input() {
+    let env = Env::default();
+    env.cost_estimate().budget().reset_unlimited();
+
+    let ranges = [(-600, 600), (-60, 60), (0, 60), (1200, 6000), (-443_580, 443_580)];
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    for (tl, tu) in ranges {
+        let lower = tick_to_sqrt_price_x64(&env, tl).unwrap();
+        let upper = tick_to_sqrt_price_x64(&env, tu).unwrap();
+        let mid = tick_to_sqrt_price_x64(&env, (tl + tu) / 2).unwrap();
+
+        for _ in 0..20 {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let amount0 = ((seed >> 20) % 1_000_000_000_000_000) as i128 + 1;
+            let amount1 = ((seed >> 10) % 1_000_000_000_000_000) as i128 + 1;
+
+            // di bawah range: cuma token0
+            let l0 = get_liquidity_for_amount0(&env, amount0, lower, upper).unwrap();
+            let (back0, back1) = get_amounts_for_liquidity(&env, l0, lower, upper, lower, false).unwrap();
+            assert!(back0 <= amount0, "amount0 {} → {}", amount0, back0);
+            assert_eq!(back1, 0);
+
+            // di atas range: cuma token1
+            let l1 = get_liquidity_for_amount1(&env, amount1, lower, upper).unwrap();
+            let (back0, back1) = get_amounts_for_liquidity(&env, l1, lower, upper, upper, false).unwrap();
+            assert_eq!(back0, 0);
+            assert!(back1 <= amount1, "amount1 {} → {}", amount1, back1);
+
+            // di tengah: L = min(L dari amount0 di atas harga, L dari amount1 di bawah harga)
+            let l = get_liquidity_for_amount0(&env, amount0, mid, upper).unwrap()
+                .min(get_liquidity_for_amount1(&env, amount1, lower, mid).unwrap());
+            let (back0, back1) = get_amounts_for_liquidity(&env, l, lower, upper, mid, false).unwrap();
+            assert!(back0 <= amount0 && back1 <= amount1);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Test auth remove_liquidity / collect: cuma owner atau operator yang
+// di-approve owner. Auth di-mock spesifik per address (bukan
+// mock_all_auths), jadi yang dicek beneran siapa yang tanda tangan.
+
+mod common;
+
+use clmm_pool::Error;
+use common::Setup;
+use soroban_sdk::{
+    testutils::{MockAuth, MockAuthInvoke},
+    Address, Env, IntoVal, Val, Vec,
+};
+
+const L: i128 = 1_000_000_000;
+
+// 1 pemanggilan pool.fn_name(args), dipasang ke MockAuth address yang tanda tangan
+fn invoke<'a>(s: &'a Setup, fn_name: &'a str, args: Vec<Val>) -> MockAuthInvoke<'a> {
+    MockAuthInvoke {
+        contract: &s.pool.address,
+        fn_name,
+        args,
+        sub_invokes: &[],
+    }
+}
+
+fn remove(s: &Setup, caller: &Address, owner: &Address, liquidity: i128) -> Result<(i128, i128), Error> {
+    let args = (caller, owner, -600i32, 600i32, liquidity, 0i128, 0i128, u64::MAX).into_val(&s.env);
+    let invoke = invoke(s, "remove_liquidity", args);
+    s.pool
+        .mock_auths(&[MockAuth { address: caller, invoke: &invoke }])
+        .try_remove_liquidity(caller, owner, &-600, &600, &liquidity, &0, &0, &u64::MAX)
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+fn collect(s: &Setup, caller: &Address, owner: &Address, recipient: &Address) -> Result<(i128, i128), Error> {
+    let args = (caller, owner, -600i32, 600i32, recipient, i128::MAX, i128::MAX).into_val(&s.env);
+    let invoke = invoke(s, "collect", args);
+    s.pool
+        .mock_auths(&[MockAuth { address: caller, invoke: &invoke }])
+        .try_collect(caller, owner, &-600, &600, recipient, &i128::MAX, &i128::MAX)
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+fn set_operator(s: &Setup, owner: &Address, operator: &Address, approved: bool) {
+    let args = (owner, operator, approved).into_val(&s.env);
+    let invoke = invoke(s, "set_operator", args);
+    s.pool
+        .mock_auths(&[MockAuth { address: owner, invoke: &invoke }])
+        .set_operator(owner, operator, &approved);
+}
+
+fn setup() -> (Setup<'static>, Address) {
+    let env = Env::default();
+    env.cost_estimate().budget().reset_unlimited();
+    let s = Setup::with_env(env);
+    let owner = s.user(1_000_000_000_000);
+    s.pool.mock_all_auths().add_liquidity(
+        &owner, &-600, &600, &L, &i128::MAX, &i128::MAX, &u64::MAX,
+    );
+    (s, owner)
+}
+
+#[test]
+fn stranger_cannot_remove_or_collect() {
+    let (s, owner) = setup();
+    let stranger = s.user(0);
+
+    assert_eq!(remove(&s, &stranger, &owner, L), Err(Error::Unauthorized));
+    assert_eq!(collect(&s, &stranger, &owner, &stranger), Err(Error::Unauthorized));
+
+    // caller ngaku owner tanpa tanda tangan owner → gagal auth
+    let res = s
+        .pool
+        .try_remove_liquidity(&owner, &owner, &-600, &600, &L, &0, &0, &u64::MAX);
+    assert!(matches!(res, Err(Err(_))));
+
+    assert_eq!(s.pool.get_position(&owner, &-600, &600).liquidity, L);
+}
+
+#[test]
+fn operator_manages_position_until_revoked() {
+    let (s, owner) = setup();
+    let operator = s.user(0);
+    let vault = s.user(0);
+
+    set_operator(&s, &owner, &operator, true);
+    assert!(s.pool.is_operator(&owner, &operator));
+
+    // operator tanda tangan sendiri, token masuk ke recipient pilihan operator
+    let (owed0, owed1) = remove(&s, &operator, &owner, L / 2).unwrap();
+    assert!(owed0 > 0 && owed1 > 0);
+    assert_eq!(collect(&s, &operator, &owner, &vault), Ok((owed0, owed1)));
+    assert_eq!(s.balances(&vault), (owed0, owed1));
+
+    // owner sendiri tetap boleh
+    assert!(remove(&s, &owner, &owner, 1).is_ok());
+
+    set_operator(&s, &owner, &operator, false);
+    assert!(!s.pool.is_operator(&owner, &operator));
+    assert_eq!(remove(&s, &operator, &owner, 1), Err(Error::Unauthorized));
+    assert_eq!(collect(&s, &operator, &owner, &vault), Err(Error::Unauthorized));
+    assert_eq!(s.pool.get_position(&owner, &-600, &600).liquidity, L / 2 - 1);
+}
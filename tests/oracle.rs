@@ -0,0 +1,159 @@
+// Test TWAP oracle: ring buffer setelah grow + muter, interpolasi
+// binary search di antara 2 observation, ObservationTooOld, dan
+// snapshot_cumulatives_inside (nilai outside di-flip waktu cross tick).
+
+mod common;
+
+use clmm_pool::Error;
+use common::Setup;
+use soroban_sdk::{testutils::Ledger, vec, Address};
+
+const BIG: i128 = 1_000_000_000_000_000;
+
+// swap ke tick `to` di ledger timestamp `time` (exact input, stop di limit)
+fn swap_at(s: &Setup, trader: &Address, time: u64, to: i32) {
+    s.env.ledger().set_timestamp(time);
+    let from = s.pool.get_pool_state().current_tick;
+    s.swap_to_tick(trader, BIG, to < from, to);
+    assert_eq!(s.pool.get_pool_state().current_tick, to);
+}
+
+#[test]
+fn observe_after_ring_buffer_wraps() {
+    let s = Setup::new();
+    let lp = s.user(BIG);
+    let trader = s.user(BIG);
+    s.add_liquidity(&lp, -6000, 6000, 1_000_000_000_000);
+
+    assert_eq!(s.pool.grow_observation_cardinality(&10), 10);
+
+    // 15 swap, tiap 100 detik, tick bolak-balik naik turun.
+    // cumulative[k] = tick_cumulative di t = 100k (model manual)
+    let mut ticks = std::vec![0i32];
+    let mut cumulative = std::vec![0i64];
+    for k in 1..=15i32 {
+        let to = if k % 2 == 1 { 60 * k } else { -60 * k };
+        swap_at(&s, &trader, 100 * k as u64, to);
+        let prev = *ticks.last().unwrap();
+        cumulative.push(cumulative.last().unwrap() + prev as i64 * 100);
+        ticks.push(to);
+    }
+
+    // 1 observation awal + 15 write di 10 slot → sudah muter:
+    // slot 0 = write ke-10 (t=1000), terbaru di slot 5 (t=1500)
+    let ps = s.pool.get_pool_state();
+    assert_eq!(ps.observation_cardinality, 10);
+    assert_eq!(ps.observation_index, 5);
+    assert_eq!(s.pool.get_observation(&0).timestamp, 1_000);
+    assert_eq!(s.pool.get_observation(&5).tick_cumulative, cumulative[15]);
+    // observation tertua = slot 6 (t=600)
+    assert_eq!(s.pool.get_observation(&6).timestamp, 600);
+
+    s.env.ledger().set_timestamp(1_550);
+    let (tick_cumulatives, seconds_per_liquidity) = s
+        .pool
+        .observe(&vec![&s.env, 0, 50, 575, 950]);
+
+    // sekarang: extrapolasi dari observation terbaru pakai tick sekarang
+    assert_eq!(tick_cumulatives.get(0).unwrap(), cumulative[15] + ticks[15] as i64 * 50);
+    // persis di observation t=1500
+    assert_eq!(tick_cumulatives.get(1).unwrap(), cumulative[15]);
+    // t=975: interpolasi antara t=900 dan t=1000 (tick selama itu = ticks[9])
+    assert_eq!(tick_cumulatives.get(2).unwrap(), cumulative[9] + ticks[9] as i64 * 75);
+    // persis di observation tertua
+    assert_eq!(tick_cumulatives.get(3).unwrap(), cumulative[6]);
+
+    // liquidity aktif konstan → seconds_per_liquidity naik terus
+    for i in 1..seconds_per_liquidity.len() {
+        assert!(seconds_per_liquidity.get(i - 1).unwrap() > seconds_per_liquidity.get(i).unwrap());
+    }
+
+    // TWAP t=1000..1500 = rata-rata tick 5 interval terakhir
+    let (twap_cumulatives, _) = s.pool.observe(&vec![&s.env, 550, 50]);
+    let twap = (twap_cumulatives.get(1).unwrap() - twap_cumulatives.get(0).unwrap()) / 500;
+    let expected: i64 = ticks[10..15].iter().map(|&t| t as i64).sum::<i64>() / 5;
+    assert_eq!(twap, expected);
+
+    // 1 detik sebelum observation tertua
+    assert_eq!(
+        s.pool.try_observe(&vec![&s.env, 951]),
+        Err(Ok(Error::ObservationTooOld))
+    );
+}
+
+#[test]
+fn observe_before_grow_only_has_latest_point() {
+    let s = Setup::new();
+    let lp = s.user(BIG);
+    let trader = s.user(BIG);
+    s.add_liquidity(&lp, -6000, 6000, 1_000_000_000_000);
+
+    swap_at(&s, &trader, 100, 600);
+    swap_at(&s, &trader, 200, -600);
+
+    // cardinality 1: cuma observation terakhir (t=200) yang disimpan
+    s.env.ledger().set_timestamp(250);
+    let (tick_cumulatives, _) = s.pool.observe(&vec![&s.env, 0, 50]);
+    assert_eq!(tick_cumulatives.get(1).unwrap(), 600 * 100);
+    assert_eq!(tick_cumulatives.get(0).unwrap(), 600 * 100 - 600 * 50);
+
+    assert_eq!(
+        s.pool.try_observe(&vec![&s.env, 51]),
+        Err(Ok(Error::ObservationTooOld))
+    );
+    // lebih tua dari timestamp 0
+    assert_eq!(
+        s.pool.try_observe(&vec![&s.env, 251]),
+        Err(Ok(Error::ObservationTooOld))
+    );
+}
+
+// -------------------------------------------------------------
+// snapshot_cumulatives_inside + nilai outside per tick
+// -------------------------------------------------------------
+
+#[test]
+fn snapshot_cumulatives_inside_follows_tick_crossings() {
+    let s = Setup::new();
+    let lp = s.user(BIG);
+    let trader = s.user(BIG);
+
+    // dua range bersebelahan, dibuat di t=0 (tick 0)
+    s.add_liquidity(&lp, -600, 600, 1_000_000_000_000);
+    s.add_liquidity(&lp, 600, 1200, 1_000_000_000_000);
+
+    assert_eq!(
+        s.pool.try_snapshot_cumulatives_inside(&1200, &1800),
+        Err(Ok(Error::TickNotInitialized))
+    );
+    assert_eq!(
+        s.pool.try_snapshot_cumulatives_inside(&-600, &610),
+        Err(Ok(Error::TickNotAligned))
+    );
+
+    // t=2100: harga naik ke tick 900 (cross 600, masuk [600, 1200))
+    swap_at(&s, &trader, 2_100, 900);
+    assert_eq!(s.pool.get_tick_info(&600).seconds_outside, 2_100);
+
+    s.env.ledger().set_timestamp(2_500);
+    // [600, 1200) aktif sejak t=2100 di tick 900
+    let (tick_cum, _, seconds) = s.pool.snapshot_cumulatives_inside(&600, &1200);
+    assert_eq!((tick_cum, seconds), (900 * 400, 400));
+    // [-600, 600) aktif t=0 .. 2100 di tick 0
+    let (tick_cum, _, seconds) = s.pool.snapshot_cumulatives_inside(&-600, &600);
+    assert_eq!((tick_cum, seconds), (0, 2_100));
+
+    // t=3000: turun ke tick 300 (cross 600 lagi → outside di-flip)
+    swap_at(&s, &trader, 3_000, 300);
+    let t600 = s.pool.get_tick_info(&600);
+    assert_eq!(t600.seconds_outside, 3_000 - 2_100);
+    assert_eq!(t600.tick_cumulative_outside, 900 * 900);
+
+    s.env.ledger().set_timestamp(3_200);
+    // [600, 1200) sekarang di atas harga: total 900 detik di tick 900
+    let (tick_cum, _, seconds) = s.pool.snapshot_cumulatives_inside(&600, &1200);
+    assert_eq!((tick_cum, seconds), (900 * 900, 900));
+    // [-600, 600): 2100 detik di tick 0 + 200 detik di tick 300
+    let (tick_cum, _, seconds) = s.pool.snapshot_cumulatives_inside(&-600, &600);
+    assert_eq!((tick_cum, seconds), (300 * 200, 2_300));
+}
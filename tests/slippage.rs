@@ -0,0 +1,158 @@
+// Test proteksi deadline + slippage di swap / add_liquidity /
+// remove_liquidity. Batas dicoba persis di nilai hasil (lolos) dan
+// lewat 1 unit (SlippageExceeded), state pool ga boleh berubah kalau gagal.
+
+mod common;
+
+use clmm_pool::{math, Error, SwapResult};
+use common::Setup;
+use soroban_sdk::{testutils::Ledger, Address};
+
+const L: i128 = 1_000_000_000_000; // posisi [-600, 600)
+const AMOUNT: i128 = 1_000_000; // swap kecil, ga nyampe limit
+const NOW: u64 = 1_000;
+
+fn setup() -> (Setup<'static>, Address, Address) {
+    let s = Setup::new();
+    s.env.ledger().set_timestamp(NOW);
+    let lp = s.user(1_000_000_000_000_000);
+    let trader = s.user(1_000_000_000_000_000);
+    s.add_liquidity(&lp, -600, 600, L);
+    (s, lp, trader)
+}
+
+// swap exact input token0 → token1
+fn try_swap(
+    s: &Setup,
+    trader: &Address,
+    amount_out_min: i128,
+    amount_in_max: i128,
+    deadline: u64,
+) -> Result<SwapResult, Error> {
+    let limit = math::tick_to_sqrt_price_x64(&s.env, -600).unwrap();
+    s.pool
+        .try_swap(trader, &AMOUNT, &true, &limit, &amount_out_min, &amount_in_max, &deadline)
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+fn try_add(
+    s: &Setup,
+    owner: &Address,
+    amount0_max: i128,
+    amount1_max: i128,
+    deadline: u64,
+) -> Result<(i128, i128), Error> {
+    s.pool
+        .try_add_liquidity(owner, &-600, &600, &L, &amount0_max, &amount1_max, &deadline)
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+fn try_remove(
+    s: &Setup,
+    owner: &Address,
+    amount0_min: i128,
+    amount1_min: i128,
+    deadline: u64,
+) -> Result<(i128, i128), Error> {
+    s.pool
+        .try_remove_liquidity(owner, owner, &-600, &600, &L, &amount0_min, &amount1_min, &deadline)
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+// amount posisi L di [-600, 600) pada harga pool sekarang
+fn position_amounts(s: &Setup, round_up: bool) -> (i128, i128) {
+    math::get_amounts_for_liquidity(
+        &s.env,
+        L,
+        math::tick_to_sqrt_price_x64(&s.env, -600).unwrap(),
+        math::tick_to_sqrt_price_x64(&s.env, 600).unwrap(),
+        s.pool.get_pool_state().sqrt_price_x64,
+        round_up,
+    )
+    .unwrap()
+}
+
+fn price_and_liquidity(s: &Setup) -> (u128, i32, i128) {
+    let ps = s.pool.get_pool_state();
+    (ps.sqrt_price_x64, ps.current_tick, ps.liquidity)
+}
+
+#[test]
+fn expired_deadline_is_rejected() {
+    let (s, lp, trader) = setup();
+    let state_before = price_and_liquidity(&s);
+
+    assert_eq!(
+        try_swap(&s, &trader, 0, i128::MAX, NOW - 1).err(),
+        Some(Error::DeadlineExpired)
+    );
+    assert_eq!(try_add(&s, &lp, i128::MAX, i128::MAX, NOW - 1), Err(Error::DeadlineExpired));
+    assert_eq!(try_remove(&s, &lp, 0, 0, NOW - 1), Err(Error::DeadlineExpired));
+    assert_eq!(price_and_liquidity(&s), state_before);
+
+    // deadline == timestamp ledger masih boleh
+    assert!(try_swap(&s, &trader, 0, i128::MAX, NOW).is_ok());
+    assert!(try_add(&s, &lp, i128::MAX, i128::MAX, NOW).is_ok());
+    assert!(try_remove(&s, &lp, 0, 0, NOW).is_ok());
+}
+
+#[test]
+fn swap_slippage_bounds() {
+    // quote dari pool yang sama persis
+    let quote = {
+        let (s, _, trader) = setup();
+        try_swap(&s, &trader, 0, i128::MAX, NOW).unwrap()
+    };
+    assert_eq!(quote.amount_in, AMOUNT);
+
+    let (s, _, trader) = setup();
+    let state_before = price_and_liquidity(&s);
+    let balances_before = s.balances(&trader);
+
+    // amount_out_min lewat 1 dari hasil
+    assert_eq!(
+        try_swap(&s, &trader, quote.amount_out + 1, i128::MAX, NOW).err(),
+        Some(Error::SlippageExceeded)
+    );
+    // amount_in_max kurang 1 dari yang ditarik
+    assert_eq!(
+        try_swap(&s, &trader, 0, quote.amount_in - 1, NOW).err(),
+        Some(Error::SlippageExceeded)
+    );
+    assert_eq!(price_and_liquidity(&s), state_before);
+    assert_eq!(s.balances(&trader), balances_before);
+
+    // batas persis di hasil → lolos
+    let r = try_swap(&s, &trader, quote.amount_out, quote.amount_in, NOW).unwrap();
+    assert_eq!((r.amount_in, r.amount_out), (quote.amount_in, quote.amount_out));
+}
+
+#[test]
+fn add_liquidity_slippage_bounds() {
+    let (s, _, _) = setup();
+    let owner = s.user(1_000_000_000_000_000);
+    let (amount0, amount1) = position_amounts(&s, true);
+    let state_before = price_and_liquidity(&s);
+
+    assert_eq!(try_add(&s, &owner, amount0 - 1, amount1, NOW), Err(Error::SlippageExceeded));
+    assert_eq!(try_add(&s, &owner, amount0, amount1 - 1, NOW), Err(Error::SlippageExceeded));
+    assert_eq!(price_and_liquidity(&s), state_before);
+
+    assert_eq!(try_add(&s, &owner, amount0, amount1, NOW), Ok((amount0, amount1)));
+}
+
+#[test]
+fn remove_liquidity_slippage_bounds() {
+    let (s, lp, _) = setup();
+    let (amount0, amount1) = position_amounts(&s, false);
+    let state_before = price_and_liquidity(&s);
+
+    assert_eq!(try_remove(&s, &lp, amount0 + 1, amount1, NOW), Err(Error::SlippageExceeded));
+    assert_eq!(try_remove(&s, &lp, amount0, amount1 + 1, NOW), Err(Error::SlippageExceeded));
+    assert_eq!(price_and_liquidity(&s), state_before);
+
+    assert_eq!(try_remove(&s, &lp, amount0, amount1, NOW), Ok((amount0, amount1)));
+}
@@ -0,0 +1,194 @@
+// Test swap loop lewat entrypoint kontrak: harga nyebrang tick
+// initialized dua arah, liquidity aktif harus ikut berubah.
+
+mod common;
+
+use clmm_pool::{math, Error};
+use common::{Setup, FEE_BPS};
+
+const L1: i128 = 1_000_000_000_000; // posisi [-600, 600)
+const L2: i128 = 2_000_000_000_000; // posisi [600, 1200)
+
+#[test]
+fn swap_crosses_adjacent_positions_both_directions() {
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    let trader = s.user(1_000_000_000_000_000);
+
+    s.add_liquidity(&lp, -600, 600, L1);
+    s.add_liquidity(&lp, 600, 1200, L2);
+    assert_eq!(s.pool.get_pool_state().liquidity, L1);
+
+    // tiap swap: saldo trader & pool berubah persis sebesar amount_in / amount_out
+    let swap = |zero_for_one: bool, limit_tick: i32| {
+        let trader_before = s.balances(&trader);
+        let pool_before = s.balances(&s.pool.address);
+
+        let r = s.swap_to_tick(&trader, 1_000_000_000_000_000, zero_for_one, limit_tick);
+        assert!(r.amount_in > 0 && r.amount_out > 0);
+
+        let (d_in, d_out) = if zero_for_one {
+            ((r.amount_in, 0), (0, r.amount_out))
+        } else {
+            ((0, r.amount_in), (r.amount_out, 0))
+        };
+        let trader_after = s.balances(&trader);
+        let pool_after = s.balances(&s.pool.address);
+        assert_eq!(trader_after.0, trader_before.0 - d_in.0 + d_out.0);
+        assert_eq!(trader_after.1, trader_before.1 - d_in.1 + d_out.1);
+        assert_eq!(pool_after.0, pool_before.0 + d_in.0 - d_out.0);
+        assert_eq!(pool_after.1, pool_before.1 + d_in.1 - d_out.1);
+
+        let ps = s.pool.get_pool_state();
+        assert_eq!(ps.current_tick, r.current_tick);
+        assert_eq!(ps.sqrt_price_x64, r.sqrt_price_x64);
+        (ps.current_tick, ps.liquidity)
+    };
+
+    // harga naik: lewat 600 (keluar posisi 1, masuk posisi 2), lalu 1200
+    assert_eq!(swap(false, 600), (600, L2));
+    assert_eq!(swap(false, 1200), (1200, 0));
+
+    // harga turun: lewat 1200 (masuk posisi 2), 600 (balik ke posisi 1), -600
+    assert_eq!(swap(true, 600), (599, L1));
+    assert_eq!(swap(true, -600), (-601, 0));
+
+    // naik lagi lewat -600 ke tengah range posisi 1
+    assert_eq!(swap(false, 0), (0, L1));
+}
+
+// Regresi: step terakhir yang ga gerakin harga (sisa input habis jadi
+// fee) ga boleh ngitung ulang tick dari harga. Harga berhenti persis di
+// sqrt(600) setelah cross 600 ke bawah → tick harus 599, bukan 600.
+#[test]
+fn swap_landing_on_crossed_tick_keeps_tick_below() {
+    const L_UPPER: i128 = 5_000_000_000_000;
+
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    let trader = s.user(1_000_000_000_000_000);
+
+    s.add_liquidity(&lp, -600, 600, L1);
+    s.add_liquidity(&lp, 600, 1200, L_UPPER);
+    s.swap_to_tick(&trader, 1_000_000_000_000_000, false, 900);
+
+    // input persis sampai sqrt(600) (+ fee step itu), plus 1 unit sisa
+    let ps = s.pool.get_pool_state();
+    let sqrt_600 = math::tick_to_sqrt_price_x64(&s.env, 600).unwrap();
+    let to_tick =
+        math::get_amount0_delta(&s.env, sqrt_600, ps.sqrt_price_x64, L_UPPER as u128, true)
+            .unwrap();
+    let fee =
+        math::mul_div_ceil(&s.env, to_tick, FEE_BPS as u128, 10_000 - FEE_BPS as u128).unwrap();
+    let amount = (to_tick + fee + 1) as i128;
+
+    let r = s.swap_to_tick(&trader, amount, true, -600);
+    assert_eq!(r.amount_in, amount);
+    assert_eq!(r.sqrt_price_x64, sqrt_600);
+
+    let ps = s.pool.get_pool_state();
+    assert_eq!((ps.current_tick, ps.liquidity), (599, L1));
+
+    // pool tetap sehat: swap naik lagi + burn posisi atas
+    assert_eq!(s.swap_to_tick(&trader, 1_000_000_000_000_000, false, 900).current_tick, 900);
+    assert_eq!(s.pool.get_pool_state().liquidity, L_UPPER);
+    s.pool.remove_liquidity(&lp, &lp, &600, &1200, &L_UPPER, &0, &0, &u64::MAX);
+    assert_eq!(s.pool.get_pool_state().liquidity, 0);
+}
+
+// Exact output (amount_specified negatif): trader dapet persis token1
+// yang diminta, token0 yang ditarik = amount_in step itu + fee.
+#[test]
+fn exact_output_swap_pays_exact_amount_out() {
+    const OUT: i128 = 1_000_000_000;
+
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    let trader = s.user(1_000_000_000_000_000);
+    s.add_liquidity(&lp, -600, 600, L1);
+
+    // masih di dalam [-600, 600) → cukup 1 step
+    let ps = s.pool.get_pool_state();
+    let limit = math::tick_to_sqrt_price_x64(&s.env, -600).unwrap();
+    let (sqrt_next, step_in, step_out, step_fee) =
+        math::compute_swap_step(&s.env, ps.sqrt_price_x64, limit, L1, -OUT, FEE_BPS).unwrap();
+    assert_eq!(step_out, OUT);
+
+    let trader_before = s.balances(&trader);
+    let pool_before = s.balances(&s.pool.address);
+
+    let r = s.pool.swap(&trader, &-OUT, &true, &limit, &OUT, &i128::MAX, &u64::MAX);
+    assert_eq!(r.amount_out, OUT);
+    assert_eq!(r.amount_in, step_in + step_fee);
+    assert_eq!(r.sqrt_price_x64, sqrt_next);
+
+    assert_eq!(s.balances(&trader), (trader_before.0 - r.amount_in, trader_before.1 + OUT));
+    assert_eq!(s.balances(&s.pool.address), (pool_before.0 + r.amount_in, pool_before.1 - OUT));
+}
+
+fn try_swap(s: &Setup, amount: i128, zero_for_one: bool, limit: u128) -> Result<i128, Error> {
+    let trader = s.user(1_000_000_000_000_000);
+    s.pool
+        .try_swap(&trader, &amount, &zero_for_one, &limit, &0, &i128::MAX, &u64::MAX)
+        .map(|r| r.unwrap().amount_out)
+        .map_err(|e| e.unwrap())
+}
+
+#[test]
+fn swap_rejects_invalid_price_limit() {
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    s.add_liquidity(&lp, -600, 600, L1);
+
+    let current = s.pool.get_pool_state().sqrt_price_x64;
+    let below = math::tick_to_sqrt_price_x64(&s.env, -60).unwrap();
+    let above = math::tick_to_sqrt_price_x64(&s.env, 60).unwrap();
+
+    // sisi yang salah (atau persis di harga sekarang)
+    let wrong_side = [(true, current), (true, above), (false, current), (false, below)];
+    for (zero_for_one, limit) in wrong_side {
+        assert_eq!(try_swap(&s, 1_000, zero_for_one, limit), Err(Error::InvalidPriceLimit));
+    }
+
+    // di luar (atau persis di) MIN / MAX_SQRT_PRICE_X64
+    for limit in [0, math::MIN_SQRT_PRICE_X64 - 1, math::MIN_SQRT_PRICE_X64] {
+        assert_eq!(try_swap(&s, 1_000, true, limit), Err(Error::InvalidPriceLimit));
+    }
+    for limit in [math::MAX_SQRT_PRICE_X64, math::MAX_SQRT_PRICE_X64 + 1, u128::MAX] {
+        assert_eq!(try_swap(&s, 1_000, false, limit), Err(Error::InvalidPriceLimit));
+    }
+
+    assert_eq!(s.pool.get_pool_state().sqrt_price_x64, current);
+    assert!(try_swap(&s, 1_000, true, below).is_ok());
+}
+
+// Limit kecapai sebelum input habis → partial fill: harga berhenti
+// persis di limit, trader cuma bayar input yang kepakai.
+#[test]
+fn swap_partial_fill_stops_at_price_limit() {
+    const AMOUNT: i128 = 1_000_000_000_000;
+
+    let s = Setup::new();
+    let lp = s.user(1_000_000_000_000_000);
+    let trader = s.user(1_000_000_000_000_000);
+    s.add_liquidity(&lp, -600, 600, L1);
+
+    let start = s.pool.get_pool_state().sqrt_price_x64;
+    let limit = math::tick_to_sqrt_price_x64(&s.env, -300).unwrap();
+    let to_limit = math::get_amount0_delta(&s.env, limit, start, L1 as u128, true).unwrap();
+    let fee = math::mul_div_ceil(&s.env, to_limit, FEE_BPS as u128, 10_000 - FEE_BPS as u128)
+        .unwrap();
+    let expected_in = (to_limit + fee) as i128;
+    assert!(expected_in < AMOUNT);
+
+    let before = s.balances(&trader);
+    let r = s.pool.swap(&trader, &AMOUNT, &true, &limit, &0, &i128::MAX, &u64::MAX);
+
+    assert_eq!(r.sqrt_price_x64, limit);
+    assert_eq!(r.current_tick, -300);
+    assert_eq!(r.amount_in, expected_in);
+    assert_eq!(s.balances(&trader), (before.0 - expected_in, before.1 + r.amount_out));
+
+    let ps = s.pool.get_pool_state();
+    assert_eq!((ps.sqrt_price_x64, ps.current_tick, ps.liquidity), (limit, -300, L1));
+}